@@ -3,35 +3,35 @@ use pokerengine::Game;
 fn main() {
     println!("Hello from an example!");
     let mut g = Game::new(5000, 100);
-    g.add_player(String::from("Alice"));
-    g.add_player(String::from("Bob"));
-    g.add_player(String::from("Charlie"));
+    let alice = g.add_player(String::from("Alice")).unwrap();
+    let bob = g.add_player(String::from("Bob")).unwrap();
+    let charlie = g.add_player(String::from("Charlie")).unwrap();
 
     // initialise deck order
     g.init_deck();
     // place blinds
-    g.force_blinds();
+    g.force_blinds().unwrap();
     // give each player 2 cards
     g.deal_hole_cards();
 
     // preflop
-    g.call(String::from("Alice"));
-    g.call(String::from("Bob"));
-    g.check(String::from("Charlie"));
+    g.call(alice).unwrap();
+    g.call(bob).unwrap();
+    g.check(charlie).unwrap();
     // flop
-    g.check(String::from("Bob"));
-    g.check(String::from("Charlie"));
-    g.check(String::from("Alice"));
+    g.check(bob).unwrap();
+    g.check(charlie).unwrap();
+    g.check(alice).unwrap();
     // turn
-    g.check(String::from("Bob"));
-    g.check(String::from("Charlie"));
-    g.check(String::from("Alice"));
+    g.check(bob).unwrap();
+    g.check(charlie).unwrap();
+    g.check(alice).unwrap();
     // river
-    g.check(String::from("Bob"));
-    g.check(String::from("Charlie"));
-    g.check(String::from("Alice"));
+    g.check(bob).unwrap();
+    g.check(charlie).unwrap();
+    g.check(alice).unwrap();
     //showdown
-    g.find_winner();
-    g.payout_winners();
+    g.find_winner().unwrap();
+    g.payout_winners().unwrap();
     g.prep_next_hand();
 }
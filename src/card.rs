@@ -1,10 +1,13 @@
+use std::fmt;
+use std::str::FromStr;
 use strum_macros::EnumIter;
 use strum::IntoEnumIterator;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use itertools::Itertools;
 
-#[derive(Debug, Eq, PartialEq, Clone, EnumIter, PartialOrd, Ord, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Eq, PartialEq, Clone, EnumIter, PartialOrd, Ord, Copy, Hash)]
 pub enum Rank {
     Ace = 14,
     King = 13,
@@ -22,7 +25,8 @@ pub enum Rank {
 }
 
 
-#[derive(Debug, Eq, PartialEq, Clone, EnumIter, PartialOrd, Ord, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Eq, PartialEq, Clone, EnumIter, PartialOrd, Ord, Copy, Hash)]
 pub enum Suit {
     Spade,
     Heart,
@@ -30,7 +34,8 @@ pub enum Suit {
     Diamond
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct Card {
     rank: Rank,
     suit: Suit
@@ -43,22 +48,225 @@ impl Card {
     }
 
     pub fn init_deck() -> Vec<Card> {
+        return Card::init_deck_with_rng(&mut thread_rng());
+    }
+
+    /// Builds a deck shuffled by `rng`, so the order can be reproduced.
+    pub fn init_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Vec<Card> {
         let mut vec = Vec::new();
         for s in Suit::iter() {
             for r in Rank::iter() {
-                vec.push(Card::new(r.clone(), s.clone()));
+                vec.push(Card::new(r, s));
             }
         }
-        vec.shuffle(&mut thread_rng());
+        vec.shuffle(rng);
         return vec;
     }
 
-    pub fn get_rank(&mut self) -> Rank {
+    /// The same seed always gives the same deck order for a given version
+    /// of `rand`.
+    pub fn init_deck_from_seed(seed: u64) -> Vec<Card> {
+        return Card::init_deck_with_rng(&mut StdRng::seed_from_u64(seed));
+    }
+
+    pub fn get_rank(&self) -> Rank {
         return self.rank;
     }
 
-    pub fn get_suit(&mut self) -> Suit {
+    pub fn get_suit(&self) -> Suit {
         return self.suit;
     }
 }
 
+/// Why a card could not be read from text.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ParseCardError {
+    /// Not one of `AKQJT98765432`.
+    InvalidRank(char),
+    /// Not one of `shcd`.
+    InvalidSuit(char),
+    /// A card must be exactly a rank followed by a suit, e.g. "Td".
+    InvalidLength(String),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCardError::InvalidRank(c) => write!(f, "invalid rank '{}', expected one of AKQJT98765432", c),
+            ParseCardError::InvalidSuit(c) => write!(f, "invalid suit '{}', expected one of s, h, c, d", c),
+            ParseCardError::InvalidLength(s) => write!(f, "invalid card \"{}\", expected a rank and a suit like \"As\"", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+impl Rank {
+    fn from_char(c: char) -> Result<Rank, ParseCardError> {
+        match c.to_ascii_uppercase() {
+            'A' => Ok(Rank::Ace),
+            'K' => Ok(Rank::King),
+            'Q' => Ok(Rank::Queen),
+            'J' => Ok(Rank::Jack),
+            'T' => Ok(Rank::Ten),
+            '9' => Ok(Rank::Nine),
+            '8' => Ok(Rank::Eight),
+            '7' => Ok(Rank::Seven),
+            '6' => Ok(Rank::Six),
+            '5' => Ok(Rank::Five),
+            '4' => Ok(Rank::Four),
+            '3' => Ok(Rank::Three),
+            '2' => Ok(Rank::Two),
+            _ => Err(ParseCardError::InvalidRank(c)),
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Rank::Ace => 'A',
+            Rank::King => 'K',
+            Rank::Queen => 'Q',
+            Rank::Jack => 'J',
+            Rank::Ten => 'T',
+            Rank::Nine => '9',
+            Rank::Eight => '8',
+            Rank::Seven => '7',
+            Rank::Six => '6',
+            Rank::Five => '5',
+            Rank::Four => '4',
+            Rank::Three => '3',
+            Rank::Two => '2',
+        }
+    }
+}
+
+impl Suit {
+    fn from_char(c: char) -> Result<Suit, ParseCardError> {
+        match c.to_ascii_lowercase() {
+            's' => Ok(Suit::Spade),
+            'h' => Ok(Suit::Heart),
+            'c' => Ok(Suit::Club),
+            'd' => Ok(Suit::Diamond),
+            _ => Err(ParseCardError::InvalidSuit(c)),
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Suit::Spade => 's',
+            Suit::Heart => 'h',
+            Suit::Club => 'c',
+            Suit::Diamond => 'd',
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Rank, ParseCardError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Rank::from_char(c),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Suit, ParseCardError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Suit::from_char(c),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Reads a card such as "As", "Td" or "7c".
+    fn from_str(s: &str) -> Result<Card, ParseCardError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(r), Some(su), None) => Ok(Card::new(Rank::from_char(r)?, Suit::from_char(su)?)),
+            _ => Err(ParseCardError::InvalidLength(s.to_string())),
+        }
+    }
+}
+
+/// Reads several cards, written either back to back ("AsKd") or separated
+/// by whitespace or commas ("Qh Jh Th").
+pub fn parse_cards(text: &str) -> Result<Vec<Card>, ParseCardError> {
+    let mut cards = Vec::new();
+    for word in text.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+        let chars: Vec<char> = word.chars().collect();
+        for pair in chars.chunks(2) {
+            let card: String = pair.iter().collect();
+            cards.push(card.parse()?);
+        }
+    }
+    return Ok(cards);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{parse_cards, Card, ParseCardError, Rank, Suit};
+
+    #[test]
+    fn cards_round_trip_through_text() {
+        for card in Card::init_deck() {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+        }
+        assert_eq!("Td".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Diamond)));
+        assert_eq!(Card::new(Rank::Seven, Suit::Club).to_string(), "7c");
+        assert_eq!("q".parse::<Rank>(), Ok(Rank::Queen));
+        assert_eq!(Suit::Heart.to_string(), "h");
+    }
+
+    #[test]
+    fn parses_card_lists() {
+        let expected = vec![Card::new(Rank::Queen, Suit::Heart), Card::new(Rank::Jack, Suit::Heart), Card::new(Rank::Ten, Suit::Heart)];
+        assert_eq!(parse_cards("Qh Jh Th"), Ok(expected.clone()));
+        assert_eq!(parse_cards("QhJhTh"), Ok(expected.clone()));
+        assert_eq!(parse_cards("Qh, Jh,Th"), Ok(expected));
+        assert_eq!(parse_cards(""), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn seeded_decks_are_reproducible() {
+        assert_eq!(Card::init_deck_from_seed(7), Card::init_deck_from_seed(7));
+        assert_ne!(Card::init_deck_from_seed(7), Card::init_deck_from_seed(8));
+        assert_eq!(Card::init_deck_from_seed(7).len(), 52);
+    }
+
+    #[test]
+    fn reports_what_is_wrong() {
+        assert_eq!("Xs".parse::<Card>(), Err(ParseCardError::InvalidRank('X')));
+        assert_eq!("Ax".parse::<Card>(), Err(ParseCardError::InvalidSuit('x')));
+        assert_eq!("Asd".parse::<Card>(), Err(ParseCardError::InvalidLength(String::from("Asd"))));
+        assert_eq!(parse_cards("AsK"), Err(ParseCardError::InvalidLength(String::from("K"))));
+        assert_eq!("1s".parse::<Card>().unwrap_err().to_string(), "invalid rank '1', expected one of AKQJT98765432");
+    }
+}
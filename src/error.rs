@@ -0,0 +1,102 @@
+use std::fmt;
+use crate::card::Card;
+use crate::{GameStreet, PlayerId};
+
+/// Why the game refused a request.
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub enum GameError {
+    /// Someone other than the player to act tried to act.
+    NotYourTurn { expected: PlayerId, got: PlayerId },
+    /// No player with that id is seated.
+    UnknownPlayer(PlayerId),
+    /// The seat number is beyond the table size.
+    NoSuchSeat(usize),
+    SeatTaken(usize),
+    TableFull,
+    /// Tables have 2 to 10 seats and must fit everyone already seated.
+    InvalidTableSize(usize),
+    /// Only allowed between hands.
+    HandInProgress,
+    /// A hand needs at least two players who are not sitting out.
+    NotEnoughPlayers,
+    /// The player is not in a straddle position, or the straddle window
+    /// has passed.
+    StraddleNotAllowed,
+    /// The player joined or sat out after this hand was dealt.
+    NotDealtIn,
+    AlreadyFolded,
+    /// The player has already closed their action on this street.
+    AlreadyActed,
+    CannotCheckFacingBet { to_call: u64 },
+    NothingToCall,
+    /// A bet was attempted where there is already one to call or raise.
+    CannotBetFacingBet { to_call: u64 },
+    /// A raise was attempted on a street where nobody has bet.
+    NoBetToRaise,
+    /// Raising is not open to the player, e.g. everyone else is all-in.
+    CannotRaise,
+    RaiseTooSmall { min: u64 },
+    /// The betting structure caps the raise below this amount.
+    RaiseTooLarge { max: u64 },
+    /// The bet is more than the player has behind.
+    InsufficientChips { stack: u64 },
+    /// The request only makes sense on another street, e.g. `find_winner`
+    /// before showdown.
+    WrongStreet { street: GameStreet },
+    /// Nobody can act until `force_blinds` has started the hand.
+    HandNotStarted,
+    /// The hand has been decided; call `prep_next_hand` first.
+    HandOver,
+    /// Preset cards can only be chosen before dealing starts.
+    CardsAlreadyDealt,
+    WrongCardCount { expected: usize, got: usize },
+    DuplicateCard(Card),
+    /// The snapshot was written by an incompatible version of the crate.
+    UnsupportedSnapshot { version: u32 },
+    /// The pot does not match what the players have committed this hand.
+    ChipsDoNotAddUp { pot: u64, committed: u64 },
+    /// Stacks and pot hold a different number of chips than were brought to
+    /// the table.
+    ChipCountChanged { expected: u64, got: u64 },
+    /// The snapshot describes a table that cannot exist.
+    CorruptSnapshot(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::NotYourTurn{expected, got} => write!(f, "it is {}'s turn, not {}'s", expected, got),
+            GameError::UnknownPlayer(id) => write!(f, "{} is not seated", id),
+            GameError::NoSuchSeat(seat) => write!(f, "there is no seat {}", seat),
+            GameError::SeatTaken(seat) => write!(f, "seat {} is taken", seat),
+            GameError::TableFull => write!(f, "every seat is taken"),
+            GameError::InvalidTableSize(seats) => write!(f, "a table cannot have {} seats", seats),
+            GameError::NotEnoughPlayers => write!(f, "a hand needs at least two players"),
+            GameError::HandInProgress => write!(f, "not allowed while a hand is being played"),
+            GameError::StraddleNotAllowed => write!(f, "straddling is not allowed here"),
+            GameError::NotDealtIn => write!(f, "player is not dealt into this hand"),
+            GameError::AlreadyFolded => write!(f, "player has already folded"),
+            GameError::AlreadyActed => write!(f, "player has already acted on this street"),
+            GameError::CannotCheckFacingBet{to_call} => write!(f, "cannot check facing a bet, {} to call", to_call),
+            GameError::NothingToCall => write!(f, "there is no bet to call"),
+            GameError::CannotBetFacingBet{to_call} => write!(f, "cannot bet facing a bet, {} to call", to_call),
+            GameError::NoBetToRaise => write!(f, "there is no bet to raise"),
+            GameError::CannotRaise => write!(f, "raising is not allowed"),
+            GameError::RaiseTooSmall{min} => write!(f, "raise must be to at least {}", min),
+            GameError::RaiseTooLarge{max} => write!(f, "raise can be to at most {}", max),
+            GameError::InsufficientChips{stack} => write!(f, "player only has {} chips", stack),
+            GameError::WrongStreet{street} => write!(f, "not allowed on {:?}", street),
+            GameError::HandNotStarted => write!(f, "the blinds have not been posted"),
+            GameError::HandOver => write!(f, "the hand is already over"),
+            GameError::CardsAlreadyDealt => write!(f, "cards have already been dealt"),
+            GameError::WrongCardCount{expected, got} => write!(f, "expected {} cards, got {}", expected, got),
+            GameError::DuplicateCard(card) => write!(f, "{} is used more than once", card),
+            GameError::UnsupportedSnapshot{version} => write!(f, "snapshot version {} is not supported", version),
+            GameError::ChipsDoNotAddUp{pot, committed} => write!(f, "pot holds {} chips but {} were committed", pot, committed),
+            GameError::ChipCountChanged{expected, got} => write!(f, "table holds {} chips but {} were brought to it", got, expected),
+            GameError::CorruptSnapshot(reason) => write!(f, "corrupt snapshot: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,369 @@
+//! Hand evaluation that works on plain cards, independent of any `Game`.
+
+use std::cmp::Ordering;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+use crate::card::{Card, Rank};
+
+mod table;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,PartialOrd,Ord,Hash)]
+pub enum HandRanking {
+    StraightFlush = 8,
+    FourOfAKind = 7,
+    FullHouse = 6,
+    Flush = 5,
+    Straight = 4,
+    ThreeOfAKind = 3,
+    TwoPair = 2,
+    Pair = 1,
+    HighCard = 0,
+}
+
+impl HandRanking {
+    fn from_value(value: u32) -> HandRanking {
+        match value {
+            8 => HandRanking::StraightFlush,
+            7 => HandRanking::FourOfAKind,
+            6 => HandRanking::FullHouse,
+            5 => HandRanking::Flush,
+            4 => HandRanking::Straight,
+            3 => HandRanking::ThreeOfAKind,
+            2 => HandRanking::TwoPair,
+            1 => HandRanking::Pair,
+            _ => HandRanking::HighCard,
+        }
+    }
+}
+
+/// The strength of a hand: its category followed by the ranks that break
+/// ties inside that category, most significant first. Comparing two values
+/// compares the hands.
+///
+/// Packed into a `u32` as the category in bits 20-23 and five 4-bit rank
+/// slots below it, so the derived ordering is the poker ordering.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,PartialOrd,Ord,Hash)]
+pub struct HandValue(u32);
+
+impl HandValue {
+    pub(crate) fn new(category: HandRanking, ranks: &[u8]) -> HandValue {
+        let mut value = (category as u32) << 20;
+        for (i, &r) in ranks.iter().take(5).enumerate() {
+            value |= (r as u32) << (16 - 4 * i);
+        }
+        HandValue(value)
+    }
+
+    pub fn category(&self) -> HandRanking {
+        return HandRanking::from_value(self.0 >> 20);
+    }
+
+    /// The tie-break ranks in comparison order, e.g. the pair rank followed
+    /// by three kickers for `Pair`, or just the top card for a straight.
+    pub fn kickers(&self) -> Vec<Rank> {
+        let mut ranks = Vec::with_capacity(5);
+        for i in 0..5 {
+            let value = (self.0 >> (16 - 4 * i)) & 0xF;
+            if let Some(rank) = Rank::iter().find(|r| *r as u32 == value) {
+                ranks.push(rank);
+            }
+        }
+        return ranks;
+    }
+}
+
+/// The algorithm used to score hands. Both produce identical values; `Table`
+/// is much faster and is what the engine uses unless told otherwise.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,Default)]
+pub enum Evaluator {
+    /// Scores all 21 five-card combinations and keeps the best.
+    Combinations,
+    /// Precomputed rank and flush tables, no allocation.
+    #[default]
+    Table,
+}
+
+impl Evaluator {
+    /// Evaluates the best five-card hand out of 5, 6 or 7 cards.
+    pub fn evaluate(&self, cards: &[Card]) -> HandValue {
+        return match self {
+            Evaluator::Combinations => best_five(cards).0,
+            Evaluator::Table => table::evaluate(cards),
+        };
+    }
+}
+
+/// Evaluates exactly five cards.
+///
+/// # Panics
+/// If `cards` does not hold five cards.
+pub fn evaluate_five(cards: &[Card]) -> HandValue {
+    assert_eq!(cards.len(), 5, "evaluate_five needs exactly five cards");
+
+    let mut ranks: Vec<u8> = cards.iter().map(|c| c.get_rank() as u8).collect();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+    let flush = cards.iter().all(|c| c.get_suit() == cards[0].get_suit());
+    let straight_high = straight_high_card(&ranks);
+
+    // (count, rank) groups, largest group first and higher rank first within a size
+    let mut groups: Vec<(u8, u8)> = Vec::with_capacity(5);
+    for &r in ranks.iter() {
+        match groups.iter_mut().find(|g| g.1 == r) {
+            Some(g) => g.0 += 1,
+            None => groups.push((1, r)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    let grouped: Vec<u8> = groups.iter().map(|g| g.1).collect();
+
+    if let (true, Some(high)) = (flush, straight_high) {
+        return HandValue::new(HandRanking::StraightFlush, &[high]);
+    }
+    match (groups[0].0, groups[1].0) {
+        (4, _) => return HandValue::new(HandRanking::FourOfAKind, &grouped),
+        (3, 2) => return HandValue::new(HandRanking::FullHouse, &grouped),
+        _ => {}
+    }
+    if flush {
+        return HandValue::new(HandRanking::Flush, &ranks);
+    }
+    if let Some(high) = straight_high {
+        return HandValue::new(HandRanking::Straight, &[high]);
+    }
+    return match (groups[0].0, groups[1].0) {
+        (3, _) => HandValue::new(HandRanking::ThreeOfAKind, &grouped),
+        (2, 2) => HandValue::new(HandRanking::TwoPair, &grouped),
+        (2, _) => HandValue::new(HandRanking::Pair, &grouped),
+        _ => HandValue::new(HandRanking::HighCard, &ranks),
+    };
+}
+
+/// Evaluates the best five-card hand out of 5, 6 or 7 cards with the
+/// default evaluator.
+///
+/// # Panics
+/// If `cards` holds fewer than five or more than seven cards.
+pub fn evaluate(cards: &[Card]) -> HandValue {
+    assert!((5..=7).contains(&cards.len()), "can only evaluate 5, 6 or 7 cards");
+    return Evaluator::default().evaluate(cards);
+}
+
+/// Like `evaluate`, but also returns the five cards making up the hand.
+pub fn best_five(cards: &[Card]) -> (HandValue, Vec<Card>) {
+    assert!((5..=7).contains(&cards.len()), "can only evaluate 5, 6 or 7 cards");
+
+    let mut best: Option<(HandValue, Vec<Card>)> = None;
+    for combo in cards.iter().cloned().combinations(5) {
+        let value = evaluate_five(&combo);
+        if best.as_ref().is_none_or(|b| value > b.0) {
+            best = Some((value, combo));
+        }
+    }
+    return best.unwrap();
+}
+
+/// Compares two hands of 5 to 7 cards each, e.g. hole cards plus board for
+/// two players at showdown. `Equal` means the pot is chopped.
+pub fn compare(a: &[Card], b: &[Card]) -> Ordering {
+    return evaluate(a).cmp(&evaluate(b));
+}
+
+/// `ranks` sorted high to low. Returns the top card of a straight, treating
+/// the wheel (A-2-3-4-5) as five high.
+fn straight_high_card(ranks: &[u8]) -> Option<u8> {
+    if ranks.windows(2).all(|w| w[0] == w[1] + 1) {
+        return Some(ranks[0]);
+    }
+    if ranks == [Rank::Ace as u8, 5, 4, 3, 2] {
+        return Some(5);
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{Card, Rank, Suit};
+    use std::cmp::Ordering;
+    use crate::eval::{best_five, compare, evaluate, evaluate_five, Evaluator, HandRanking};
+
+    fn hand(cards: &[(Rank, Suit)]) -> Vec<Card> {
+        cards.iter().map(|&(r, s)| Card::new(r, s)).collect()
+    }
+
+    fn parse(text: &str) -> Vec<Card> {
+        crate::card::parse_cards(text).unwrap()
+    }
+
+    /// (first hand, second hand, result for the first hand)
+    const HEAD_TO_HEAD: &[(&str, &str, Ordering)] = &[
+        // high card is decided by the first differing card, not the sum
+        ("As 7d 5h 4c 2s", "Kh Qd 9s 8c 6h", Ordering::Greater),
+        ("Ah Kd 9s 7c 3h", "As Kc 9d 7h 2s", Ordering::Greater),
+        ("Ah Kd 9s 7c 3h", "As Kc 9d 7h 3s", Ordering::Equal),
+        ("Kh Qd Js 9c 8h", "Ks Qc Jd 9h 7s", Ordering::Greater),
+        // pair: pair rank, then each kicker in turn
+        ("8s 8d Ah 4c 3s", "7h 7c As Kd Qh", Ordering::Greater),
+        ("8s 8d Ah 4c 3s", "8h 8c Kd Qh Js", Ordering::Greater),
+        ("8s 8d Ah Kc 3s", "8h 8c Ad Kh 2s", Ordering::Greater),
+        ("8s 8d Ah Kc 3s", "8h 8c Ad Kh 3c", Ordering::Equal),
+        ("2s 2d Ah Kc Qs", "3h 3c 4d 5h 7s", Ordering::Less),
+        // two pair: top pair, bottom pair, kicker
+        ("Ks Kd 2h 2c 3s", "Qh Qc Jd Js As", Ordering::Greater),
+        ("Ks Kd 5h 5c 3s", "Kh Kc 4d 4s As", Ordering::Greater),
+        ("Ks Kd 5h 5c 3s", "Kh Kc 5d 5s 4s", Ordering::Less),
+        ("Ks Kd 5h 5c 3s", "Kh Kc 5d 5s 3d", Ordering::Equal),
+        // three of a kind
+        ("9s 9d 9h 2c 3s", "8h 8c 8d As Ks", Ordering::Greater),
+        ("9s 9d 9h Ac 3s", "9h 9c 9d Ks Qs", Ordering::Greater),
+        ("9s 9d 9h Ac 4s", "9h 9c 9d As 3s", Ordering::Greater),
+        // straights, with the wheel the lowest
+        ("6s 5d 4h 3c 2s", "As 2d 3h 4c 5s", Ordering::Greater),
+        ("As Kd Qh Jc Ts", "Ks Qd Jh Tc 9s", Ordering::Greater),
+        ("As 2d 3h 4c 5s", "Ah 2c 3d 4s 5h", Ordering::Equal),
+        ("As 2d 3h 4c 5s", "Kh Kc Kd 2s 3h", Ordering::Greater),
+        // flushes compare card by card
+        ("As 7s 5s 4s 2s", "Kh Qh 9h 8h 6h", Ordering::Greater),
+        ("Ah Qh 9h 8h 6h", "As Qs 9s 8s 5s", Ordering::Greater),
+        ("Ah Qh 9h 8h 6h", "As Qs 9s 8s 6s", Ordering::Equal),
+        ("2h 3h 4h 5h 7h", "As Kd Qh Jc Ts", Ordering::Greater),
+        // full house: trips first, then the pair
+        ("3s 3d 3h 2c 2s", "2h 2c 2d As Ad", Ordering::Greater),
+        ("Qs Qd Qh 2c 2s", "Qh Qc Qd Jc Jd", Ordering::Less),
+        ("Qs Qd Qh Jc Js", "Qh Qc Qd Jd Jh", Ordering::Equal),
+        // four of a kind and its kicker
+        ("5s 5d 5h 5c 2s", "4h 4c 4d 4s As", Ordering::Greater),
+        ("5s 5d 5h 5c Ks", "5h 5c 5d 5s Qd", Ordering::Greater),
+        ("2s 2d 2h 2c 3s", "As Ad Ah Kc Ks", Ordering::Greater),
+        // straight flushes, with the steel wheel the lowest
+        ("6h 5h 4h 3h 2h", "Ah 2h 3h 4h 5h", Ordering::Greater),
+        ("As Ks Qs Js Ts", "Kh Qh Jh Th 9h", Ordering::Greater),
+        ("Ad 2d 3d 4d 5d", "As Ah Ac Ad Ks", Ordering::Greater),
+        ("9c 8c 7c 6c 5c", "9d 8d 7d 6d 5d", Ordering::Equal),
+        // seven cards: best five only, extra cards never count
+        ("As Ad Kh Kc Qs Qd 2c", "As Ad Kh Kc Js Jd 9c", Ordering::Greater),
+        ("As Ad Kh Kc 7s 3d 2c", "As Ad Kh Kc 7d 6d 5c", Ordering::Equal),
+        ("Ts 9d 8h 7c 6s 5d 4c", "Ts 9d 8h 7c 6s 2d 2c", Ordering::Equal),
+        ("Ks Qs 9s 5s 3s 2s 2d", "Ks Qs 9s 5s 3s As 2d", Ordering::Less),
+        ("7s 7d 7h 7c As 2d 3c", "7s 7d 7h 7c Ks Qd Jc", Ordering::Greater),
+        ("8s 8d 8h 5c 5s 5d 2c", "8s 8d 8h 6c 6s 2d 3c", Ordering::Less),
+    ];
+
+    #[test]
+    fn head_to_head_results() {
+        for &(first, second, expected) in HEAD_TO_HEAD {
+            assert_eq!(compare(&parse(first), &parse(second)), expected, "{} vs {}", first, second);
+            assert_eq!(compare(&parse(second), &parse(first)), expected.reverse(), "{} vs {}", second, first);
+        }
+    }
+
+    #[test]
+    fn every_five_card_hand_is_classified_by_both_evaluators() {
+        use itertools::Itertools;
+        use std::collections::{HashMap, HashSet};
+
+        let deck = crate::card::Card::init_deck();
+        let mut counts: HashMap<HandRanking, u32> = HashMap::new();
+        let mut distinct = HashSet::new();
+        for five in deck.iter().cloned().combinations(5) {
+            let value = evaluate_five(&five);
+            assert_eq!(Evaluator::Table.evaluate(&five), value, "{:?}", five);
+            *counts.entry(value.category()).or_insert(0) += 1;
+            distinct.insert(value);
+        }
+        assert_eq!(counts[&HandRanking::StraightFlush], 40);
+        assert_eq!(counts[&HandRanking::FourOfAKind], 624);
+        assert_eq!(counts[&HandRanking::FullHouse], 3744);
+        assert_eq!(counts[&HandRanking::Flush], 5108);
+        assert_eq!(counts[&HandRanking::Straight], 10200);
+        assert_eq!(counts[&HandRanking::ThreeOfAKind], 54912);
+        assert_eq!(counts[&HandRanking::TwoPair], 123552);
+        assert_eq!(counts[&HandRanking::Pair], 1098240);
+        assert_eq!(counts[&HandRanking::HighCard], 1302540);
+        // the number of distinct five-card poker hands
+        assert_eq!(distinct.len(), 7462);
+    }
+
+    #[test]
+    fn table_matches_combinations_on_seven_card_hands() {
+        for seed in 0..5000 {
+            let deck = crate::card::Card::init_deck_from_seed(seed);
+            let seven = &deck[..7];
+            assert_eq!(Evaluator::Table.evaluate(seven), Evaluator::Combinations.evaluate(seven), "seed {}: {:?}", seed, seven);
+            assert_eq!(Evaluator::Table.evaluate(&seven[..6]), Evaluator::Combinations.evaluate(&seven[..6]));
+        }
+    }
+
+    #[test]
+    fn categories_order_every_hand_of_a_lower_category_below() {
+        let ladder = [
+            "7d 5c 4h 3s 2d",
+            "2s 2d 3h 4c 5h 7c",
+            "2s 2d 3h 3c 4d",
+            "2s 2d 2h 3c 4d",
+            "As 2d 3h 4c 5s",
+            "2h 3h 4h 5h 7h",
+            "2s 2d 2h 3c 3d",
+            "2s 2d 2h 2c 3d",
+            "Ah 2h 3h 4h 5h",
+        ];
+        for pair in ladder.windows(2) {
+            assert_eq!(compare(&parse(pair[0]), &parse(pair[1])), Ordering::Less, "{} vs {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn detects_every_category() {
+        use Rank::*;
+        use Suit::*;
+        let cases = [
+            (hand(&[(Nine, Heart), (Ten, Heart), (Jack, Heart), (Queen, Heart), (King, Heart)]), HandRanking::StraightFlush),
+            (hand(&[(Four, Club), (Four, Heart), (Four, Spade), (Four, Diamond), (King, Heart)]), HandRanking::FourOfAKind),
+            (hand(&[(Four, Club), (Four, Heart), (Four, Spade), (King, Diamond), (King, Heart)]), HandRanking::FullHouse),
+            (hand(&[(Two, Club), (Seven, Club), (Nine, Club), (Jack, Club), (King, Club)]), HandRanking::Flush),
+            (hand(&[(Ace, Club), (Two, Heart), (Three, Spade), (Four, Diamond), (Five, Heart)]), HandRanking::Straight),
+            (hand(&[(Four, Club), (Four, Heart), (Four, Spade), (Nine, Diamond), (King, Heart)]), HandRanking::ThreeOfAKind),
+            (hand(&[(Four, Club), (Four, Heart), (Nine, Spade), (Nine, Diamond), (King, Heart)]), HandRanking::TwoPair),
+            (hand(&[(Four, Club), (Four, Heart), (Eight, Spade), (Nine, Diamond), (King, Heart)]), HandRanking::Pair),
+            (hand(&[(Two, Club), (Four, Heart), (Eight, Spade), (Nine, Diamond), (King, Heart)]), HandRanking::HighCard),
+        ];
+        for (cards, category) in cases.iter() {
+            assert_eq!(evaluate_five(cards).category(), *category, "{:?}", cards);
+        }
+    }
+
+    #[test]
+    fn kickers_are_reported_in_comparison_order() {
+        use Rank::*;
+        use Suit::*;
+        let two_pair = evaluate_five(&hand(&[(Four, Club), (King, Heart), (Nine, Spade), (Four, Diamond), (Nine, Heart)]));
+        assert_eq!(two_pair.kickers(), vec![Nine, Four, King]);
+
+        let wheel = evaluate_five(&hand(&[(Ace, Club), (Two, Heart), (Three, Spade), (Four, Diamond), (Five, Heart)]));
+        assert_eq!(wheel.kickers(), vec![Five]);
+    }
+
+    #[test]
+    fn picks_the_best_five_of_seven() {
+        use Rank::*;
+        use Suit::*;
+        let cards = hand(&[(Ace, Spade), (Ace, Heart), (King, Spade), (Queen, Spade), (Jack, Spade), (Ten, Spade), (Two, Club)]);
+        let (value, five) = best_five(&cards);
+        assert_eq!(value.category(), HandRanking::StraightFlush);
+        assert_eq!(value.kickers(), vec![Ace]);
+        assert!(!five.contains(&Card::new(Ace, Heart)));
+        assert_eq!(evaluate(&cards), evaluate_five(&five));
+    }
+
+    #[test]
+    fn values_compare_directly() {
+        use Rank::*;
+        use Suit::*;
+        let trips = evaluate(&hand(&[(Four, Club), (Four, Heart), (Four, Spade), (Nine, Diamond), (King, Heart), (Two, Club)]));
+        let two_pair = evaluate(&hand(&[(Ace, Club), (Ace, Heart), (King, Spade), (King, Diamond), (Queen, Heart), (Two, Club)]));
+        assert!(trips > two_pair);
+    }
+}
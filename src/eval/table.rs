@@ -0,0 +1,137 @@
+//! Table-driven evaluator. Cards are folded into one 13-bit rank mask per
+//! suit, and every category is then read off those masks with bit operations
+//! and two precomputed tables indexed by a rank mask. Nothing is allocated.
+
+use crate::card::Card;
+use crate::eval::{HandRanking, HandValue};
+
+const MASKS: usize = 1 << 13;
+
+/// Top card of the highest straight in a rank mask, 0 if there is none.
+static STRAIGHT_HIGH: [u8; MASKS] = build_straight_high();
+
+/// The five highest ranks in a rank mask, packed as `HandValue` kicker slots.
+static TOP_FIVE: [u32; MASKS] = build_top_five();
+
+const fn build_straight_high() -> [u8; MASKS] {
+    let mut table = [0u8; MASKS];
+    let mut mask = 0;
+    while mask < MASKS {
+        // ace to ten high: five consecutive bits ending at the top card
+        let mut top = 12;
+        while top >= 4 {
+            let run = 0b11111 << (top - 4);
+            if mask & run == run {
+                table[mask] = top as u8 + 2;
+                break;
+            }
+            top -= 1;
+        }
+        // the wheel, A-2-3-4-5
+        if table[mask] == 0 && mask & 0b1_0000_0000_1111 == 0b1_0000_0000_1111 {
+            table[mask] = 5;
+        }
+        mask += 1;
+    }
+    table
+}
+
+const fn build_top_five() -> [u32; MASKS] {
+    let mut table = [0u32; MASKS];
+    let mut mask = 0;
+    while mask < MASKS {
+        let mut packed = 0;
+        let mut slot = 0;
+        let mut bit = 12i32;
+        while bit >= 0 && slot < 5 {
+            if mask & (1 << bit) != 0 {
+                packed |= ((bit as u32) + 2) << (16 - 4 * slot);
+                slot += 1;
+            }
+            bit -= 1;
+        }
+        table[mask] = packed;
+        mask += 1;
+    }
+    table
+}
+
+/// The highest rank in a non-empty mask, as a single bit.
+fn top_bit(mask: u16) -> u16 {
+    return 1 << (15 - mask.leading_zeros());
+}
+
+/// Rank value (2-14) of the single bit in `bit`.
+fn rank_of(bit: u16) -> u32 {
+    return bit.trailing_zeros() + 2;
+}
+
+/// `count` kickers taken from the top of `mask`, placed after `offset`
+/// slots that are already used.
+fn kickers(mask: u16, count: u32, offset: u32) -> u32 {
+    let keep = !((1u32 << (20 - 4 * (offset + count))) - 1) & 0xF_FFFF;
+    return (TOP_FIVE[mask as usize] >> (4 * offset)) & keep;
+}
+
+fn value(category: HandRanking, slots: u32) -> HandValue {
+    return HandValue(((category as u32) << 20) | slots);
+}
+
+/// Evaluates 5 to 7 cards.
+pub fn evaluate(cards: &[Card]) -> HandValue {
+    debug_assert!((5..=7).contains(&cards.len()), "can only evaluate 5, 6 or 7 cards");
+
+    let mut suits = [0u16; 4];
+    for c in cards {
+        suits[c.get_suit() as usize] |= 1 << (c.get_rank() as u16 - 2);
+    }
+
+    // with at most seven cards a flush rules out quads and full houses
+    for &suited in suits.iter() {
+        if suited.count_ones() >= 5 {
+            let high = STRAIGHT_HIGH[suited as usize] as u32;
+            if high != 0 {
+                return value(HandRanking::StraightFlush, high << 16);
+            }
+            return value(HandRanking::Flush, TOP_FIVE[suited as usize]);
+        }
+    }
+
+    let [a, b, c, d] = suits;
+    let ranks = a | b | c | d;
+    let fours = a & b & c & d;
+    let threes = (a & b & c) | (a & b & d) | (a & c & d) | (b & c & d);
+    let pairs = (a & b) | (a & c) | (a & d) | (b & c) | (b & d) | (c & d);
+
+    if fours != 0 {
+        let quad = top_bit(fours);
+        return value(HandRanking::FourOfAKind, rank_of(quad) << 16 | kickers(ranks & !quad, 1, 1));
+    }
+    if threes != 0 {
+        let trips = top_bit(threes);
+        let others = pairs & !trips;
+        if others != 0 {
+            let pair = top_bit(others);
+            return value(HandRanking::FullHouse, rank_of(trips) << 16 | rank_of(pair) << 12);
+        }
+    }
+    let high = STRAIGHT_HIGH[ranks as usize] as u32;
+    if high != 0 {
+        return value(HandRanking::Straight, high << 16);
+    }
+    if threes != 0 {
+        let trips = top_bit(threes);
+        return value(HandRanking::ThreeOfAKind, rank_of(trips) << 16 | kickers(ranks & !trips, 2, 1));
+    }
+    if pairs != 0 {
+        let top = top_bit(pairs);
+        let rest = pairs & !top;
+        if rest != 0 {
+            let second = top_bit(rest);
+            return value(HandRanking::TwoPair,
+                         rank_of(top) << 16 | rank_of(second) << 12 | kickers(ranks & !top & !second, 1, 2));
+        }
+        return value(HandRanking::Pair, rank_of(top) << 16 | kickers(ranks & !top, 3, 1));
+    }
+    return value(HandRanking::HighCard, TOP_FIVE[ranks as usize]);
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+use crate::card::Card;
+use crate::{GameStreet, PlayerAction, PlayerId};
+
+/// Something that happened at the table, delivered to every `GameListener`
+/// in the order it happened.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub enum GameEvent {
+    /// Blinds are about to be posted. `button` is a seat number.
+    HandStarted { hand_number: u64, button: u64 },
+    AntePosted { player: PlayerId, amount: u64 },
+    /// A small or big blind, including blinds owed after sitting out.
+    BlindPosted { player: PlayerId, amount: u64 },
+    StraddlePosted { player: PlayerId, amount: u64 },
+    /// Carries the cards, so only forward it to the player it is for.
+    HoleCardsDealt { player: PlayerId, cards: Vec<Card> },
+    /// `amount` is the chips the action put in the pot; bets and raises
+    /// carry the new street total.
+    PlayerActed { player: PlayerId, action: PlayerAction, amount: u64 },
+    StreetDealt { street: GameStreet, cards: Vec<Card> },
+    /// One per main or side pot. Odd chips go to the first winners listed.
+    PotAwarded { amount: u64, winners: Vec<PlayerId> },
+    HandEnded { hand_number: u64 },
+    /// The player busted and left the table before the next hand.
+    PlayerEliminated { player: PlayerId },
+}
+
+/// Receives a game's events as they happen. Closures taking a `&GameEvent`
+/// are listeners too. Listeners must be `Send` so the game can move between
+/// threads.
+pub trait GameListener: Send {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+impl<F: FnMut(&GameEvent) + Send> GameListener for F {
+    fn on_event(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Listeners(Vec<Box<dyn GameListener>>);
+
+impl Listeners {
+    pub(crate) fn push(&mut self, listener: Box<dyn GameListener>) {
+        self.0.push(listener);
+    }
+
+    pub(crate) fn emit(&mut self, event: GameEvent) {
+        for listener in self.0.iter_mut() {
+            listener.on_event(&event);
+        }
+    }
+}
+
+/// A cloned game starts with no listeners.
+impl Clone for Listeners {
+    fn clone(&self) -> Listeners {
+        return Listeners::default();
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} listeners", self.0.len())
+    }
+}
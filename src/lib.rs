@@ -1,43 +1,145 @@
-use std::cmp::Reverse;
-use std::ptr::null;
-use strum_macros::EnumIter;
-use strum::IntoEnumIterator;
-use rand::thread_rng;
-use rand::seq::SliceRandom;
-use itertools::{cloned, Itertools};
-use crate::card::{Card, Rank};
-use crate::HandRanking::{FourOfAKind, StraightFlush};
-
-mod card;
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::eval::{Evaluator, HandValue};
+
+pub mod card;
+pub mod eval;
+mod error;
+mod event;
+mod view;
+mod snapshot;
+
+pub use error::GameError;
+pub use event::{GameEvent, GameListener};
+pub use view::{PlayerView, SeatView, SpectatorView};
+pub use snapshot::{Snapshot, SNAPSHOT_VERSION};
+use event::Listeners;
+
+/// Stable identity of a player for the lifetime of a `Game`, independent of
+/// their display name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,Hash,PartialOrd,Ord)]
+pub struct PlayerId(pub usize);
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "player {}", self.0)
+    }
+}
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Eq,PartialEq,Debug,Clone)]
 pub struct Player {
+    pub id: PlayerId,
+    /// Display name only; players are identified by `id`.
     pub name: String,
+    pub seat: usize,
+    /// Skipped for blinds and dealing from the next hand on.
+    pub sitting_out: bool,
+    /// Blinds that passed while sitting out, owed on return.
+    pub missed_small_blind: bool,
+    pub missed_big_blind: bool,
+    dealt_in: bool,
     chip_stack: u64,
     current_bet: u64,
     has_folded: bool,
     final_action: bool,
+    /// The bet this street when the player last checked, called or raised.
+    acted_at: Option<u64>,
+    total_bet: u64,
     hole_cards: Vec<card::Card>,
-    strongest_combo: Vec<card::Card>,
-    hand_rank: HandRanking
+    /// Hole cards have been turned face up at showdown.
+    shown: bool,
+    preset_hole_cards: Vec<card::Card>,
+    hand_value: Option<HandValue>
 }
 
 impl Player {
-    fn new(name:String, chip_stack:u64) -> Player {
-        Player{name, chip_stack, current_bet: 0, has_folded: false, final_action: false, hole_cards: Vec::with_capacity(2), strongest_combo: Vec::new(), hand_rank: HandRanking::HighCard }
+    fn new(id:PlayerId, name:String, seat:usize, chip_stack:u64) -> Player {
+        Player{id, name, seat, sitting_out: false, missed_small_blind: false, missed_big_blind: false, dealt_in: true, chip_stack, current_bet: 0, total_bet: 0, has_folded: false, final_action: false, acted_at: None, hole_cards: Vec::with_capacity(2), shown: false, preset_hole_cards: Vec::new(), hand_value: None }
+    }
+
+    /// Dealt into the current hand and not folded.
+    fn is_live(&self) -> bool {
+        return self.dealt_in && !self.has_folded;
     }
 }
 
-#[derive(Debug)]
-enum ActionType {
+/// What kind of entry an `Action` in the hand history is.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy)]
+pub enum ActionType {
     CHECK,
     CALL,
     FOLD,
+    BET,
     RAISE,
-    BLIND
+    /// A bet, raise or call that puts the player all-in.
+    ALLIN,
+    BLIND,
+    ANTE,
+    STRADDLE,
+    /// Board cards dealt for a street, with no player.
+    DEAL
+}
+
+/// How the button and blinds move when seats empty out.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,Default)]
+pub enum ButtonRule {
+    /// The button always moves to the next player, so a player may skip
+    /// or repeat a blind after eliminations.
+    #[default]
+    Moving,
+    /// The big blind always moves to the next player and the small blind
+    /// and button follow the seats that posted last hand, even if they are
+    /// now empty (a dead small blind or dead button).
+    Dead,
+}
+
+/// Antes collected before the blinds. They go in the pot but do not count
+/// towards calling.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,Default)]
+pub enum Ante {
+    #[default]
+    None,
+    /// Every player dealt in posts this much.
+    PerPlayer(u64),
+    /// The big blind posts this much for the whole table.
+    BigBlind(u64),
 }
 
-#[derive(Debug,PartialEq,Clone)]
+/// Seats allowed to post a voluntary straddle of twice the big blind.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy)]
+pub enum StraddlePosition {
+    /// The first player to act after the big blind.
+    UnderTheGun,
+    /// The button, also known as a Mississippi straddle.
+    Button,
+}
+
+/// How much a player may bet or raise.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy,Default)]
+pub enum BettingStructure {
+    #[default]
+    NoLimit,
+    /// A raise can be at most the size of the pot after calling.
+    PotLimit,
+    /// Bets and raises are exactly `small_bet` preflop and on the flop and
+    /// `big_bet` on the turn and river. After the opening bet (the big blind
+    /// preflop) a street allows `max_raises` raises, unless only two players
+    /// are left in the hand.
+    FixedLimit { small_bet: u64, big_bet: u64, max_raises: u32 },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
 pub enum GameStreet {
     PRE,
     FLOP,
@@ -46,38 +148,94 @@ pub enum GameStreet {
     SHOWDOWN
 }
 
-#[derive(Debug,Eq,PartialEq,Clone)]
-enum HandRanking {
-    StraightFlush = 8,
-    FourOfAKind = 7,
-    FullHouse = 6,
-    Flush = 5,
-    Straight = 4,
-    ThreeOfAKind = 3,
-    TwoPair = 2,
-    Pair = 1,
-    HighCard = 0,
-}
-
-
-#[derive(Debug)]
+/// One entry in the hand history, `Game::actions`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Clone)]
 pub struct Action {
     action: ActionType,
-    player: Player,
+    player: Option<PlayerId>,
     bet_size: u64,
-    street: GameStreet
+    street: GameStreet,
+    cards: Vec<card::Card>
+}
+
+impl Action {
+    pub fn action(&self) -> ActionType {
+        return self.action;
+    }
+
+    pub fn player(&self) -> Option<PlayerId> {
+        return self.player;
+    }
+
+    /// Chips the action put in the pot.
+    pub fn bet_size(&self) -> u64 {
+        return self.bet_size;
+    }
+
+    pub fn street(&self) -> &GameStreet {
+        return &self.street;
+    }
+
+    /// Board cards dealt, for `DEAL` entries.
+    pub fn cards(&self) -> &[card::Card] {
+        return &self.cards;
+    }
 }
 
-#[derive(Debug)]
-pub struct InvalidActionError;
+/// A decision by the player to act, as sent to `Game::apply`. Amounts for
+/// `Bet` and `RaiseTo` are the player's total for the street.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone,Copy)]
+pub enum PlayerAction {
+    Fold,
+    Check,
+    Call,
+    /// Opens the betting on a street where nobody has bet yet.
+    Bet(u64),
+    /// Raises an existing bet to this total.
+    RaiseTo(u64),
+    AllIn,
+}
 
-#[derive(Debug)]
+/// A main or side pot and the players who can still win it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub struct Pot {
+    pub amount: u64,
+    pub eligible: Vec<PlayerId>
+}
+
+/// What the player whose turn it is may do. Raise amounts are "raise to"
+/// totals for the street, i.e. including what the player already has in
+/// front of them.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub struct LegalActions {
+    pub player: PlayerId,
+    pub can_check: bool,
+    /// Chips needed to call, capped at the player's stack. Zero when checking.
+    pub call_amount: u64,
+    /// Smallest legal raise, or the all-in amount if the stack is shorter.
+    /// `None` when the player cannot raise.
+    pub min_raise_to: Option<u64>,
+    pub max_raise_to: Option<u64>,
+}
+
+/// A table of `max_seats` seats. `players` holds only the occupied seats,
+/// ordered by seat number, and `button` is a seat number.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Clone)]
 pub struct Game {
     pub players: Vec<Player>,
     pub start_stack: u64,
     pub button: u64,
     pub actions: Vec<Action>,
     pub big_blind: u64,
+    pub ante: Ante,
+    /// Empty unless straddling is allowed at this table.
+    pub allowed_straddles: Vec<StraddlePosition>,
+    pub betting_structure: BettingStructure,
     pub pot: u64,
     pub previous_raise: u64,
     pub previous_bet: u64,
@@ -86,499 +244,602 @@ pub struct Game {
     pub street: GameStreet,
     pub deck: Vec<card::Card>,
     pub board: Vec<card::Card>,
-    pub winners: Vec<Player>,
+    pub winners: Vec<PlayerId>,
+    pub evaluator: Evaluator,
+    pub button_rule: ButtonRule,
+    pub session_seed: Option<u64>,
+    pub hand_number: u64,
+    preset_board: Vec<card::Card>,
+    /// Cards burned before each street this hand.
+    burned: Vec<card::Card>,
+    pub hand_complete: bool,
+    next_player_id: usize,
+    max_seats: usize,
+    raises_this_street: u32,
+    /// Chips in the pot that belong to no player's contribution, such as a
+    /// big blind ante or a dead small blind. They go to the main pot.
+    dead_money: u64,
+    /// Chips brought to the table by everyone seated, wherever they are now.
+    table_chips: u64,
+    /// Small and big blind seats of the last hand.
+    last_blinds: Option<(usize, usize)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    listeners: Listeners,
 }
 
+// a table must stay movable between threads, listeners and all
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Game>();
+};
+
 impl Game {
     pub fn new(start_stack:u64, big_blind:u64) -> Game {
-        Game{players: Vec::with_capacity(9), start_stack, button:0, actions: Vec::new(), big_blind, pot: 0, previous_raise: 0, previous_bet: 0, current_bet: 0, turn_marker: 1, street: GameStreet::PRE, deck: Vec::new(), board: Vec::with_capacity(5), winners: Vec::new() }
+        Game{players: Vec::with_capacity(9), start_stack, button:0, actions: Vec::new(), big_blind, ante: Ante::default(), allowed_straddles: Vec::new(), betting_structure: BettingStructure::default(), pot: 0, previous_raise: 0, previous_bet: 0, current_bet: 0, turn_marker: 1, street: GameStreet::PRE, deck: Vec::new(), board: Vec::with_capacity(5), winners: Vec::new(), evaluator: Evaluator::default(), button_rule: ButtonRule::default(), session_seed: None, hand_number: 1, preset_board: Vec::new(), burned: Vec::new(), hand_complete: false, next_player_id: 0, max_seats: 9, raises_this_street: 0, dead_money: 0, table_chips: 0, last_blinds: None, listeners: Listeners::default() }
     }
 
-    pub fn add_player(&mut self, name:String) {
-        self.players.push(Player::new(name, self.start_stack));
+    /// A game whose every shuffle is derived from `seed` and the hand
+    /// number, so a whole session can be replayed exactly.
+    pub fn with_seed(start_stack:u64, big_blind:u64, seed:u64) -> Game {
+        let mut game = Game::new(start_stack, big_blind);
+        game.session_seed = Some(seed);
+        return game;
     }
 
-    pub fn deal_hole_cards(&mut self) {
-        let original_turn_marker = self.turn_marker;
+    /// Seats a new player in the lowest free seat.
+    pub fn add_player(&mut self, name:String) -> Result<PlayerId, GameError> {
+        let seat = (0..self.max_seats)
+            .find(|&s| self.players.iter().all(|p| p.seat != s))
+            .ok_or(GameError::TableFull)?;
+        return self.seat_player(name, seat);
+    }
 
-        self.turn_marker = self.button+1;
-        for i in (1..3).step_by(1) {
-            for j in (1..self.players.len()+1).step_by(1) {
-                self.increment_turn();
-                self.players[self.turn_marker as usize].hole_cards.push(self.deck.pop().unwrap());
-            }
+    /// Seats a new player in `seat`. Someone joining mid-hand is dealt in
+    /// from the next hand.
+    pub fn seat_player(&mut self, name:String, seat:usize) -> Result<PlayerId, GameError> {
+        if seat >= self.max_seats {
+            return Err(GameError::NoSuchSeat(seat))
+        }
+        if self.players.iter().any(|p| p.seat == seat) {
+            return Err(GameError::SeatTaken(seat))
         }
-        self.turn_marker = original_turn_marker;
+        let id = PlayerId(self.next_player_id);
+        self.next_player_id += 1;
+        let mut player = Player::new(id, name, seat, self.start_stack);
+        self.table_chips += self.start_stack;
+        player.dealt_in = !self.hand_in_progress();
+        let at = self.players.iter().position(|p| p.seat > seat).unwrap_or(self.players.len());
+        self.players.insert(at, player);
+        // keep the turn with the same player
+        if self.hand_in_progress() && at <= self.turn_marker as usize {
+            self.turn_marker += 1;
+        }
+        return Ok(id);
     }
 
-    pub fn init_deck(&mut self) {
-        self.deck = card::Card::init_deck();
+    /// Takes a player off the table. Not allowed while a hand is being
+    /// played.
+    pub fn remove_player(&mut self, id: PlayerId) -> Result<Player, GameError> {
+        let player = self.index_of(id)?;
+        if self.hand_in_progress() {
+            return Err(GameError::HandInProgress)
+        }
+        let player = self.players.remove(player);
+        self.table_chips -= player.chip_stack;
+        self.turn_marker = 0;
+        return Ok(player);
     }
 
-    pub fn payout_winners(&mut self) {
-        let payout = self.pot / (self.winners.len() as u64);
-        for w in self.winners.iter() {
-            for p in self.players.iter_mut() {
-                if w.name == p.name {
-                    p.chip_stack += payout;
-                }
-            }
-        }
+    /// The player keeps their seat but is not dealt in and posts no blinds
+    /// until they sit back in. A hand already under way is played out.
+    pub fn sit_out(&mut self, id: PlayerId) -> Result<(), GameError> {
+        let player = self.index_of(id)?;
+        self.players[player].sitting_out = true;
+        Ok(())
     }
 
-    pub fn prep_next_hand(&mut self) {
-        self.increment_button();
-        self.winners.clear();
-        self.board.clear();
-        self.deck.clear();
-        self.actions.clear();
-        self.pot = 0;
-        self.previous_raise = 0;
-        self.previous_bet = 0;
-        self.current_bet = 0;
-        self.turn_marker = self.button + 1;
-        self.street = GameStreet::PRE;
-        for p in self.players.iter_mut() {
-            p.current_bet = 0;
-            p.has_folded = false;
-            p.final_action = false;
-            p.hole_cards.clear();
-            p.strongest_combo.clear();
-            p.hand_rank = HandRanking::HighCard;
-        }
+    pub fn sit_in(&mut self, id: PlayerId) -> Result<(), GameError> {
+        let player = self.index_of(id)?;
+        self.players[player].sitting_out = false;
+        Ok(())
+    }
 
+    pub fn max_seats(&self) -> usize {
+        return self.max_seats;
     }
 
-    pub fn find_winner(&mut self) -> Result<(), InvalidActionError> {
-        if self.street != GameStreet::SHOWDOWN {
-            return Err(InvalidActionError);
+    /// Sets the table size, between 2 and 10 seats. Occupied seats must still
+    /// fit.
+    pub fn set_max_seats(&mut self, seats: usize) -> Result<(), GameError> {
+        if !(2..=10).contains(&seats) || self.players.iter().any(|p| p.seat >= seats) {
+            return Err(GameError::InvalidTableSize(seats))
         }
-        let mut possible_winners = Vec::new();
-        for p in self.players.iter(){
-            if !(p.has_folded) {
-                possible_winners.push(p.clone());
-            }
-        }
-        let mut best_rank = HandRanking::HighCard;
+        self.max_seats = seats;
+        Ok(())
+    }
 
-        let mut i = 0;
+    fn hand_in_progress(&self) -> bool {
+        return !self.actions.is_empty() && !self.hand_complete;
+    }
 
-        while i < self.players.len() {
-            let best_hand = self.evaluate_hand(self.players[i].clone().hole_cards);
-            self.players[i].strongest_combo = best_hand.clone();
-            self.players[i].hand_rank = self.rank_five_card_combo(best_hand.clone());
+    pub fn player(&self, id: PlayerId) -> Option<&Player> {
+        return self.players.iter().find(|p| p.id == id);
+    }
 
-            if self.players[i].hand_rank.clone() as u8 > best_rank.clone() as u8 {
-                best_rank = self.players[i].hand_rank.clone();
-            }
-            i += 1;
-        }
+    /// Changes the display name shown for `id`. Names need not be unique.
+    pub fn rename_player(&mut self, id: PlayerId, name: String) -> Result<(), GameError> {
+        let player = self.index_of(id)?;
+        self.players[player].name = name;
+        Ok(())
+    }
 
-        for mut p in possible_winners.iter_mut() {
-            let best_hand = self.evaluate_hand(p.clone().hole_cards);
-            p.strongest_combo = best_hand.clone();
-            p.hand_rank = self.rank_five_card_combo(best_hand.clone());
-            if p.hand_rank.clone() as u8 > best_rank.clone() as u8 {
-                best_rank = p.hand_rank.clone();
-            }
+    /// Registers `listener` to receive every `GameEvent` from now on.
+    pub fn subscribe(&mut self, listener: Box<dyn GameListener>) {
+        self.listeners.push(listener);
+    }
+
+    /// Adds `action` to the hand history and tells listeners about it.
+    fn record(&mut self, action: Action) {
+        let amount = action.bet_size;
+        let acted = |player, decision| GameEvent::PlayerActed{player, action: decision, amount};
+        let event = match (&action.action, action.player) {
+            (ActionType::DEAL, _) => GameEvent::StreetDealt{street: action.street.clone(), cards: action.cards.clone()},
+            (ActionType::ANTE, Some(player)) => GameEvent::AntePosted{player, amount},
+            (ActionType::BLIND, Some(player)) => GameEvent::BlindPosted{player, amount},
+            (ActionType::STRADDLE, Some(player)) => GameEvent::StraddlePosted{player, amount},
+            (ActionType::CHECK, Some(player)) => acted(player, PlayerAction::Check),
+            (ActionType::CALL, Some(player)) => acted(player, PlayerAction::Call),
+            (ActionType::FOLD, Some(player)) => acted(player, PlayerAction::Fold),
+            (ActionType::BET, Some(player)) => acted(player, PlayerAction::Bet(self.current_bet)),
+            (ActionType::RAISE, Some(player)) => acted(player, PlayerAction::RaiseTo(self.current_bet)),
+            (ActionType::ALLIN, Some(player)) => acted(player, PlayerAction::AllIn),
+            (_, None) => unreachable!("only deals have no player"),
+        };
+        self.listeners.emit(event);
+        self.actions.push(action);
+    }
+
+    fn index_of(&self, id: PlayerId) -> Result<usize, GameError> {
+        return self.players.iter().position(|p| p.id == id).ok_or(GameError::UnknownPlayer(id));
+    }
+
+    pub fn deal_hole_cards(&mut self) {
+        // presets of players sitting out stay in the deck
+        let mut preset = self.preset_board.clone();
+        for p in self.players.iter().filter(|p| p.dealt_in) {
+            preset.extend(p.preset_hole_cards.iter().cloned());
         }
-        possible_winners.retain(|x| x.hand_rank == best_rank.clone());
-        if possible_winners.len() == 1 {
-            self.winners.push(possible_winners[0].clone());
-        } else if best_rank == HandRanking::StraightFlush {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_straight(p.clone().strongest_combo) {
-                    highest_rank = self.rank_straight(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_straight(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::FourOfAKind {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_four_of_a_kind(p.clone().strongest_combo) {
-                    highest_rank = self.rank_four_of_a_kind(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_four_of_a_kind(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::FullHouse {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_full_house(p.clone().strongest_combo) {
-                    highest_rank = self.rank_full_house(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_full_house(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::Flush {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_flush(p.clone().strongest_combo) {
-                    highest_rank = self.rank_flush(p.clone().strongest_combo);
+        self.deck.retain(|c| !preset.contains(c));
+
+        // one card at a time, starting left of the button
+        let start = self.button_position() + 1;
+        let seats = self.players.len();
+        for round in 0..2 {
+            for i in (start..start + seats).map(|i| i % seats) {
+                let player = &mut self.players[i];
+                if !player.dealt_in {
+                    continue;
                 }
+                let card = match player.preset_hole_cards.get(round) {
+                    Some(c) => c.clone(),
+                    None => self.deck.pop().unwrap(),
+                };
+                player.hole_cards.push(card);
             }
-            for p in possible_winners.clone() {
-                if self.rank_flush(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::Straight {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_straight(p.clone().strongest_combo) {
-                    highest_rank = self.rank_straight(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_straight(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::ThreeOfAKind {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_three_of_a_kind(p.clone().strongest_combo) {
-                    highest_rank = self.rank_three_of_a_kind(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_three_of_a_kind(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
-            }
-        } else if best_rank == HandRanking::TwoPair {
-            let mut highest_rank = 0;
-            for p in possible_winners.clone() {
-                if highest_rank < self.rank_two_pair(p.clone().strongest_combo) {
-                    highest_rank = self.rank_two_pair(p.clone().strongest_combo);
-                }
-            }
-            for p in possible_winners.clone() {
-                if self.rank_two_pair(p.clone().strongest_combo) == highest_rank {
-                    self.winners.push(p.clone());
-                }
+        }
+        for i in (start..start + seats).map(|i| i % seats) {
+            if self.players[i].dealt_in {
+                let event = GameEvent::HoleCardsDealt{player: self.players[i].id, cards: self.players[i].hole_cards.clone()};
+                self.listeners.emit(event);
             }
         }
-        Ok(())
+        // blinds may already have put everyone but one player all-in
+        self.progress_street();
     }
 
-    fn rank_high_card(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        for h in hand.clone() {
-            rank += h.clone().get_rank() as u8;
+    /// Fixes the hole cards `id` will be dealt this hand. Must be called
+    /// before `deal_hole_cards`; everything not preset is still dealt from
+    /// the shuffled deck.
+    pub fn set_hole_cards(&mut self, id:PlayerId, cards: Vec<card::Card>) -> Result<(), GameError> {
+        if self.players.iter().any(|p| !p.hole_cards.is_empty()) {
+            return Err(GameError::CardsAlreadyDealt)
         }
-        return rank as u64;
-    }
-
-    fn rank_pair(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 {
-            rank = hand[0].clone().get_rank() as u64 * 1000 + hand[2].clone().get_rank() as u64 + hand[3].clone().get_rank() as u64 + hand[4].clone().get_rank() as u64;
-        } else if hand[1].clone().get_rank() as u64 == hand[2].clone().get_rank() as u64 {
-            rank = hand[1].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 + hand[3].clone().get_rank() as u64 + hand[4].clone().get_rank() as u64;
-        } else if hand[2].clone().get_rank() as u64 == hand[3].clone().get_rank() as u64 {
-            rank = hand[2].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 + hand[1].clone().get_rank() as u64 + hand[4].clone().get_rank() as u64;
-        } else if hand[3].clone().get_rank() as u64 == hand[4].clone().get_rank() as u64 {
-            rank = hand[3].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 + hand[1].clone().get_rank() as u64 + hand[2].clone().get_rank() as u64;
+        if cards.len() != 2 {
+            return Err(GameError::WrongCardCount{expected: 2, got: cards.len()})
         }
-        return rank as u64;
-    }
-
-    fn rank_two_pair(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 &&
-           hand[2].clone().get_rank() as u8 == hand[3].clone().get_rank() as u8 {
-            rank = hand[2].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 * 100 + hand[4].clone().get_rank() as u64;
-        } else if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 &&
-           hand[3].clone().get_rank() as u8 == hand[4].clone().get_rank() as u8 {
-            rank = hand[3].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 * 100 + hand[2].clone().get_rank() as u64;
-        } else if hand[1].clone().get_rank() as u8 == hand[2].clone().get_rank() as u8 &&
-           hand[3].clone().get_rank() as u8 == hand[4].clone().get_rank() as u8 {
-            rank = hand[3].clone().get_rank() as u64 * 1000 + hand[1].clone().get_rank() as u64 * 100 + hand[0].clone().get_rank() as u64;
+        let player = self.index_of(id)?;
+        let mut taken = self.preset_cards();
+        taken.retain(|c| !self.players[player].preset_hole_cards.contains(c));
+        if cards[0] == cards[1] {
+            return Err(GameError::DuplicateCard(cards[0].clone()))
         }
-        return rank as u64;
+        if let Some(c) = cards.iter().find(|c| taken.contains(c)) {
+            return Err(GameError::DuplicateCard(c.clone()))
+        }
+        self.players[player].preset_hole_cards = cards;
+        Ok(())
     }
 
-    fn rank_three_of_a_kind(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 &&
-           hand[1].clone().get_rank() as u8 == hand[2].clone().get_rank() as u8 {
-            rank = hand[0].clone().get_rank() as u64 * 1000 + hand[3].clone().get_rank() as u64 + hand[4].clone().get_rank() as u64;
-        } else if hand[1].clone().get_rank() as u8 == hand[2].clone().get_rank() as u8 &&
-           hand[2].clone().get_rank() as u8 == hand[3].clone().get_rank() as u8 {
-            rank = hand[1].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 + hand[4].clone().get_rank() as u64;
-        } else if hand[2].clone().get_rank() as u8 == hand[3].clone().get_rank() as u8 &&
-           hand[3].clone().get_rank() as u8 == hand[4].clone().get_rank() as u8 {
-            rank = hand[2].clone().get_rank() as u64 * 1000 + hand[0].clone().get_rank() as u64 + hand[1].clone().get_rank() as u64;
-
+    /// Fixes the first cards of the board, in order flop, turn, river. Must
+    /// be called before `deal_hole_cards`.
+    pub fn set_board(&mut self, cards: Vec<card::Card>) -> Result<(), GameError> {
+        if self.players.iter().any(|p| !p.hole_cards.is_empty()) {
+            return Err(GameError::CardsAlreadyDealt)
+        }
+        if cards.len() > 5 {
+            return Err(GameError::WrongCardCount{expected: 5, got: cards.len()})
+        }
+        let mut taken: Vec<card::Card> = self.players.iter()
+            .flat_map(|p| p.preset_hole_cards.iter().cloned())
+            .collect();
+        for c in cards.iter() {
+            if taken.contains(c) {
+                return Err(GameError::DuplicateCard(c.clone()))
+            }
+            taken.push(c.clone());
         }
-        return rank as u64;
+        self.preset_board = cards;
+        Ok(())
     }
 
-    fn rank_flush(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        for h in hand.clone() {
-            rank += h.clone().get_rank() as u8;
+    fn preset_cards(&self) -> Vec<card::Card> {
+        let mut cards = self.preset_board.clone();
+        for p in self.players.iter() {
+            cards.extend(p.preset_hole_cards.iter().cloned());
         }
-        return rank as u64;
+        return cards;
     }
 
-    fn rank_full_house(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 && hand[1].clone().get_rank() as u8 == hand[2].clone().get_rank() as u8 {
-            rank = (hand[2].clone().get_rank() as u64 * 1000) + hand[4].clone().get_rank() as u64;
-        } else {
-            rank = (hand[3].clone().get_rank() as u64 * 1000) + hand[0].clone().get_rank() as u64;
-        }
-        return rank as u64
+    fn burn(&mut self) {
+        let card = self.deck.pop().unwrap();
+        self.burned.push(card);
     }
 
-    fn rank_four_of_a_kind(&mut self, hand: Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[0].clone().get_rank() as u8 == hand[1].clone().get_rank() as u8 {
-            rank = (hand[2].clone().get_rank() as u64 * 1000) + hand[4].clone().get_rank() as u64;
-        } else {
-            rank = (hand[2].clone().get_rank() as u64 * 1000) + hand[0].clone().get_rank() as u64;
-        }
-        return rank as u64;
+    fn deal_board_card(&mut self) {
+        let card = match self.preset_board.get(self.board.len()) {
+            Some(c) => c.clone(),
+            None => self.deck.pop().unwrap(),
+        };
+        self.board.push(card);
     }
 
-    fn rank_straight(&mut self, hand:Vec<card::Card>) -> u64 {
-        let mut rank = 0;
-        if hand[4].clone().get_rank() == Rank::Ace && hand[0].clone().get_rank() == Rank::Two {
-            rank = 1;
-        } else {
-            rank = hand[2].clone().get_rank() as u8;
+    /// Shuffles a fresh deck, seeded from the session seed if there is one.
+    pub fn init_deck(&mut self) {
+        match self.session_seed {
+            Some(seed) => {
+                let mut bytes = [0u8; 32];
+                bytes[..8].copy_from_slice(&seed.to_le_bytes());
+                bytes[8..16].copy_from_slice(&self.hand_number.to_le_bytes());
+                self.init_deck_with_rng(&mut StdRng::from_seed(bytes));
+            }
+            None => self.deck = card::Card::init_deck(),
         }
-        return rank as u64;
     }
 
-    fn evaluate_hand(&mut self, hand: Vec<card::Card>) -> Vec<card::Card> {
-        let mut cards = self.board.clone();
-        cards.append(&mut hand.clone());
-        cards.sort_by_key(|c| c.clone().get_suit());
-        cards.sort_by_key(|c| c.clone().get_rank());
-        let it = cards.into_iter().combinations(5);
+    pub fn init_deck_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.deck = card::Card::init_deck_with_rng(rng);
+    }
 
-        let mut best_hand_rank = HandRanking::HighCard;
-        let mut best_hands = Vec::new();
+    pub fn init_deck_from_seed(&mut self, seed: u64) {
+        self.deck = card::Card::init_deck_from_seed(seed);
+    }
 
-        for i in it {
-            let r  = self.rank_five_card_combo(i.clone());
-            if (r.clone() as u8) > (best_hand_rank.clone() as u8) {
-                best_hand_rank = r.clone();
-                best_hands.clear();
-                best_hands.push(i.clone());
-            } else if (r.clone() as u8) == (best_hand_rank.clone() as u8) {
-                best_hands.push(i.clone());
+    /// Splits everything committed this hand into a main pot and side pots.
+    /// Each pot level is set by the total contribution of a player still in
+    /// the hand, so an all-in player can only win what they covered.
+    pub fn pots(&self) -> Vec<Pot> {
+        let mut levels: Vec<u64> = self.players.iter()
+            .filter(|p| !p.has_folded && p.total_bet > 0)
+            .map(|p| p.total_bet)
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut pots: Vec<Pot> = Vec::new();
+        let mut previous_level = 0;
+        for level in levels {
+            let mut amount = 0;
+            let mut eligible = Vec::new();
+            for p in self.players.iter() {
+                amount += p.total_bet.min(level) - p.total_bet.min(previous_level);
+                if !p.has_folded && p.total_bet >= level {
+                    eligible.push(p.id);
+                }
             }
+            pots.push(Pot{amount, eligible});
+            previous_level = level;
         }
 
-        if (best_hand_rank == HandRanking::HighCard) || best_hand_rank == HandRanking::Pair || best_hand_rank == HandRanking::TwoPair || best_hand_rank == HandRanking::ThreeOfAKind || best_hand_rank == HandRanking::Flush || best_hand_rank == HandRanking::FullHouse || best_hand_rank == HandRanking::FourOfAKind {
-            let best_hand = best_hands[best_hands.len()-1].clone();
-            return best_hand.clone();
-        } else if best_hand_rank == HandRanking::Straight || best_hand_rank == HandRanking::StraightFlush {
-            let best_hand = self.best_straight_card_combo(best_hands.clone());
-            return best_hand.clone();
-        }
-        return Vec::new();
-
-    }
-
-    fn rank_five_card_combo(&mut self, mut cards: Vec<card::Card>) -> HandRanking {
-        cards.sort_by_key(|c| c.clone().get_suit());
-        cards.sort_by_key(|c| c.clone().get_rank());
-
-        if self.is_straight_flush(cards.clone()) {
-            return HandRanking::StraightFlush;
-        } else if self.is_four_of_a_kind(cards.clone()) {
-            return HandRanking::FourOfAKind;
-        } else if self.is_full_house(cards.clone()) {
-            return HandRanking::FullHouse;
-        } else if self.is_flush(cards.clone()) {
-            return HandRanking::Flush;
-        } else if self.is_straight(cards.clone()) {
-            return HandRanking::Straight
-        } else if self.is_three_of_a_kind(cards.clone()) {
-            return HandRanking::ThreeOfAKind
-        } else if self.is_two_pair(cards.clone()) {
-            return HandRanking::TwoPair
-        } else if self.is_pair(cards.clone()) {
-            return HandRanking::Pair
-        }
-        return HandRanking::HighCard
-    }
-
-    fn best_straight_card_combo(&mut self, mut combos: Vec<Vec<card::Card>>) -> Vec<card::Card> {
-        if combos.len() > 1 {
-            let mut highest_rank = 0;
-            let mut winning_combo = Vec::new();
-            for combo in combos.clone() {
-                if combo[2].clone().get_rank() as u8 > highest_rank {
-                    highest_rank = combo[2].clone().get_rank() as u8;
-                    winning_combo.clear();
-                    winning_combo = combo.clone();
+        // chips from folded players above every live player's contribution
+        let unclaimed: u64 = self.players.iter()
+            .map(|p| p.total_bet - p.total_bet.min(previous_level))
+            .sum();
+        if let Some(last) = pots.last_mut() {
+            last.amount += unclaimed;
+        }
+        if let Some(main) = pots.first_mut() {
+            main.amount += self.dead_money;
+        }
+        return pots;
+    }
+
+    /// Awards each pot to the best hand among its eligible players, ranking
+    /// the hands first if `find_winner` has not. Does nothing once the hand
+    /// has been paid out.
+    pub fn payout_winners(&mut self) -> Result<(), GameError> {
+        if self.hand_complete {
+            return Ok(());
+        }
+        if self.players.iter().any(|p| p.is_live() && p.hand_value.is_none()) {
+            self.find_winner()?;
+        }
+        self.hand_complete = true;
+        for pot in self.pots() {
+            let eligible: Vec<usize> = pot.eligible.iter()
+                .filter_map(|&id| self.index_of(id).ok())
+                .collect();
+            let mut winners = self.best_hands(&eligible);
+            if winners.is_empty() {
+                continue;
+            }
+            // odd chips go to the winners closest to the left of the button
+            let seats = self.max_seats;
+            let button = self.button as usize;
+            winners.sort_by_key(|&i| (self.players[i].seat + seats - button - 1) % seats);
+
+            let ids = winners.iter().map(|&i| self.players[i].id).collect();
+            self.listeners.emit(GameEvent::PotAwarded{amount: pot.amount, winners: ids});
+
+            let payout = pot.amount / (winners.len() as u64);
+            let mut odd_chips = pot.amount % (winners.len() as u64);
+            for w in winners {
+                self.players[w].chip_stack += payout;
+                if odd_chips > 0 {
+                    self.players[w].chip_stack += 1;
+                    odd_chips -= 1;
                 }
             }
-            return winning_combo.clone();
         }
-        return combos[0].clone();
+        self.listeners.emit(GameEvent::HandEnded{hand_number: self.hand_number});
+        Ok(())
     }
 
-    fn is_pair(&mut self, cards: Vec<card::Card>) -> bool {
-        if cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8 {
-            return true;
-        } else if cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8 {
-            return true;
-        } else if cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8 {
-            return true;
-        } else if cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8 {
-            return true;
+    /// Clears the table for the next hand. Busted players leave the table
+    /// before the button moves.
+    pub fn prep_next_hand(&mut self) {
+        let busted: Vec<PlayerId> = self.players.iter().filter(|p| p.chip_stack == 0).map(|p| p.id).collect();
+        for player in busted {
+            self.listeners.emit(GameEvent::PlayerEliminated{player});
+        }
+        self.players.retain(|p| p.chip_stack > 0);
+        // the next hand's blinds set the turn; until then keep it in range
+        self.turn_marker = 0;
+        self.increment_button();
+        self.hand_number += 1;
+        self.winners.clear();
+        self.board.clear();
+        self.deck.clear();
+        self.preset_board.clear();
+        self.burned.clear();
+        self.actions.clear();
+        self.pot = 0;
+        self.previous_raise = 0;
+        self.previous_bet = 0;
+        self.current_bet = 0;
+        self.raises_this_street = 0;
+        self.dead_money = 0;
+        self.street = GameStreet::PRE;
+        self.hand_complete = false;
+        for p in self.players.iter_mut() {
+            p.dealt_in = !p.sitting_out;
+            p.current_bet = 0;
+            p.total_bet = 0;
+            p.has_folded = false;
+            p.final_action = false;
+            p.acted_at = None;
+            p.hole_cards.clear();
+            p.shown = false;
+            p.preset_hole_cards.clear();
+            p.hand_value = None;
         }
-        return false;
+
     }
 
-    fn is_two_pair(&mut self, cards: Vec<card::Card>) -> bool {
-        if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-            (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) &&
-            (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-            (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
+    pub fn find_winner(&mut self) -> Result<(), GameError> {
+        if self.hand_complete {
+            return Err(GameError::HandOver);
+        }
+        if self.street != GameStreet::SHOWDOWN {
+            return Err(GameError::WrongStreet{street: self.street.clone()});
         }
-        return false;
+        let mut contenders = Vec::new();
+        for i in 0..self.players.len() {
+            if !self.players[i].is_live() {
+                continue;
+            }
+            let mut cards = self.board.clone();
+            cards.extend(self.players[i].hole_cards.iter().cloned());
+            self.players[i].hand_value = Some(self.evaluator.evaluate(&cards));
+            self.players[i].shown = true;
+            contenders.push(i);
+        }
+        self.winners = self.best_hands(&contenders)
+            .into_iter()
+            .map(|i| self.players[i].id)
+            .collect();
+        Ok(())
     }
 
-    fn is_three_of_a_kind(&mut self, cards: Vec<card::Card>) -> bool {
-        if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-            (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) &&
-            (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) &&
-            (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
-        }
-        return false;
+    /// Everyone else has folded: the last player takes the whole pot without
+    /// a showdown and no more board cards are dealt.
+    fn award_uncontested(&mut self) {
+        let winner = self.players.iter().position(|p| p.is_live()).unwrap();
+        self.players[winner].chip_stack += self.pot;
+        self.winners = vec![self.players[winner].id];
+        self.hand_complete = true;
+        self.listeners.emit(GameEvent::PotAwarded{amount: self.pot, winners: self.winners.clone()});
+        self.listeners.emit(GameEvent::HandEnded{hand_number: self.hand_number});
     }
 
-    fn is_straight(&mut self, cards: Vec<card::Card>) -> bool {
-        if cards[4].clone().get_rank() == Rank::Ace && cards[0].clone().get_rank() == Rank::Two {
-            for i in 0..3 {
-                if (cards[i].clone().get_rank() as u8 + 1 as u8) != (cards[i+1].clone().get_rank() as u8) {
-                    return false;
-                }
-            }
-            return true;
-        }
+    /// Returns the indices of the players holding the strongest hand among
+    /// `contenders`. Hands must already have been ranked by `find_winner`.
+    fn best_hands(&self, contenders: &[usize]) -> Vec<usize> {
+        let best = contenders.iter()
+            .map(|&i| self.players[i].hand_value)
+            .max()
+            .flatten();
+        return contenders.iter()
+            .copied()
+            .filter(|&i| self.players[i].hand_value == best)
+            .collect();
+    }
 
-        for i in 0..4 {
-            if (cards[i].clone().get_rank() as u8 + 1 as u8) != (cards[i+1].clone().get_rank() as u8) {
-                return false;
-            }
+    fn reset_current_bet(&mut self) {
+        for p in self.players.iter_mut() {
+            p.current_bet = 0;
+            p.acted_at = None;
         }
-        return true;
     }
 
-    fn is_flush(&mut self, cards: Vec<card::Card>) -> bool {
-        let first_suit = cards[0].clone().get_suit();
-        for c in cards.clone() {
-            if c.clone().get_suit() != first_suit {
-                return false;
-            }
-        }
-        return true;
+    /// The blinds for this hand have been posted, so there is a turn to
+    /// take.
+    fn hand_started(&self) -> bool {
+        return (self.turn_marker as usize) < self.players.len()
+            && self.actions.iter().any(|a| matches!(a.action, ActionType::BLIND));
     }
 
-    fn is_full_house(&mut self, cards: Vec<card::Card>) -> bool {
-        if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-            (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) &&
-            (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-            (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) &&
-            (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
+    /// The options open to the player whose turn it is, or `None` when nobody
+    /// is waiting to act.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        if !self.hand_started() || self.hand_complete || self.street == GameStreet::SHOWDOWN {
+            return None;
+        }
+        let player = self.turn_marker as usize;
+        let p = &self.players[player];
+        if !p.dealt_in || p.has_folded || p.final_action || p.chip_stack == 0 {
+            return None;
         }
-        return false;
+
+        let to_call = self.current_bet.saturating_sub(p.current_bet);
+        let all_in_to = p.current_bet + p.chip_stack;
+        let (min_raise_to, max_raise_to) = self.raise_bounds(to_call);
+        // a player who has acted may only raise again once the bet has gone
+        // up by a full raise since, possibly over several short all-ins
+        let reopened = match p.acted_at {
+            Some(level) => self.current_bet - level >= min_raise_to - self.current_bet,
+            None => true,
+        };
+        // raising needs chips beyond a call and someone left to call it
+        let can_raise = reopened && all_in_to > self.current_bet && self.players_able_to_act() > 1 && !self.raises_capped();
+
+        return Some(LegalActions{
+            player: p.id,
+            can_check: to_call == 0,
+            call_amount: to_call.min(p.chip_stack),
+            min_raise_to: if can_raise { Some(min_raise_to.min(all_in_to)) } else { None },
+            max_raise_to: if can_raise { Some(max_raise_to.min(all_in_to)) } else { None },
+        });
     }
 
-    fn is_four_of_a_kind(&mut self, cards: Vec<card::Card>) -> bool {
-        if (cards[0].clone().get_rank() as u8 == cards[1].clone().get_rank() as u8) &&
-           (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) &&
-           (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) {
-            return true;
-        } else if (cards[1].clone().get_rank() as u8 == cards[2].clone().get_rank() as u8) &&
-           (cards[2].clone().get_rank() as u8 == cards[3].clone().get_rank() as u8) &&
-           (cards[3].clone().get_rank() as u8 == cards[4].clone().get_rank() as u8) {
-            return true;
+    /// The smallest full bet or raise and the largest one the betting
+    /// structure allows, as street totals and ignoring stack sizes. A full
+    /// raise is at least the size of the last bet or raise on the street,
+    /// and never less than the big blind.
+    fn raise_bounds(&self, to_call: u64) -> (u64, u64) {
+        let min_raise_to = self.current_bet + self.previous_raise.max(self.big_blind);
+        match self.betting_structure {
+            BettingStructure::NoLimit => (min_raise_to, u64::MAX),
+            BettingStructure::PotLimit => {
+                // every bet on this street is already in the pot
+                let pot_after_call = self.pot + to_call;
+                (min_raise_to, self.current_bet + pot_after_call)
+            }
+            BettingStructure::FixedLimit{small_bet, big_bet, ..} => {
+                let size = match self.street {
+                    GameStreet::PRE | GameStreet::FLOP => small_bet,
+                    _ => big_bet,
+                };
+                (self.current_bet + size, self.current_bet + size)
+            }
         }
-        return false;
     }
 
-    fn is_straight_flush(&mut self, cards: Vec<card::Card>) -> bool {
-        if self.is_straight(cards.clone()) && self.is_flush(cards.clone()) {
-            return true;
+    /// True once a fixed-limit street has had all the raises it allows.
+    fn raises_capped(&self) -> bool {
+        match self.betting_structure {
+            BettingStructure::FixedLimit{max_raises, ..} => {
+                self.raises_this_street >= max_raises && self.players.iter().filter(|p| p.is_live()).count() > 2
+            }
+            _ => false,
         }
-        return false;
     }
 
-    fn reset_current_bet(&mut self) {
-        for mut p in self.players.iter_mut() {
-            p.current_bet = 0;
+    /// Players who have not folded and still have chips behind.
+    fn players_able_to_act(&self) -> usize {
+        return self.players.iter().filter(|p| p.is_live() && p.chip_stack > 0).count();
+    }
+
+    /// True once nobody is left with a decision on this street: everyone who
+    /// can still bet has acted, or at most one such player remains and they
+    /// are not facing a bet.
+    fn betting_closed(&self) -> bool {
+        let mut able = self.players.iter().filter(|p| p.is_live() && p.chip_stack > 0);
+        if self.players_able_to_act() <= 1 {
+            return able.all(|p| p.final_action || p.current_bet >= self.current_bet);
         }
+        return able.all(|p| p.final_action);
     }
 
     fn progress_street(&mut self) {
-        for p in self.players.iter() {
-            if !p.final_action {
-                return;
-            }
+        if self.hand_complete || !self.betting_closed() {
+            return;
+        }
+        self.deal_next_street();
+        // all-ins have closed the betting, so run out the board to showdown
+        while self.street != GameStreet::SHOWDOWN && self.betting_closed() {
+            self.deal_next_street();
         }
+    }
+
+    fn deal_next_street(&mut self) {
+        let dealt = self.board.len();
         if self.street == GameStreet::PRE {
             self.street = GameStreet::FLOP;
-            self.deck.pop();
-            self.board.push(self.deck.pop().unwrap());
-            self.board.push(self.deck.pop().unwrap());
-            self.board.push(self.deck.pop().unwrap());
-            self.turn_marker = self.button+1
+            self.burn();
+            self.deal_board_card();
+            self.deal_board_card();
+            self.deal_board_card();
         } else if self.street == GameStreet::FLOP {
             self.street = GameStreet::TURN;
-            self.deck.pop();
-            self.board.push(self.deck.pop().unwrap());
-            self.turn_marker = self.button+1
+            self.burn();
+            self.deal_board_card();
         } else if self.street == GameStreet::TURN {
             self.street = GameStreet::RIVER;
-            self.deck.pop();
-            self.board.push(self.deck.pop().unwrap());
-            self.turn_marker = self.button+1
+            self.burn();
+            self.deal_board_card();
         } else if self.street == GameStreet::RIVER {
             self.street = GameStreet::SHOWDOWN;
         } else if self.street == GameStreet::SHOWDOWN {
             return;
         }
+        if self.board.len() > dealt {
+            let deal = Action{
+                action: ActionType::DEAL,
+                player: None,
+                bet_size: 0,
+                street: self.street.clone(),
+                cards: self.board[dealt..].to_vec()
+            };
+            self.record(deal);
+        }
         self.reset_final_action();
         self.reset_current_bet();
-        self.previous_raise = self.big_blind;
-        self.turn_marker = self.button+1;
+        self.turn_marker = self.button_position() as u64;
+        self.next_to_act();
         self.previous_bet = 0;
         self.current_bet = 0;
         self.previous_raise = 0;
+        self.raises_this_street = 0;
 
     }
 
@@ -588,31 +849,225 @@ impl Game {
         }
         self.players[self.turn_marker as usize].chip_stack -= bet;
         self.players[self.turn_marker as usize].current_bet = bet;
-        self.current_bet = bet;
+        self.players[self.turn_marker as usize].total_bet += bet;
+        self.current_bet = self.current_bet.max(bet);
         self.pot += bet;
         self.previous_raise = self.big_blind;
         self.previous_bet = bet;
         let forced_blind = Action{
             action: ActionType::BLIND,
-            player: self.players[self.turn_marker as usize].clone(),
+            player: Some(self.players[self.turn_marker as usize].id),
             bet_size: bet,
-            street: self.street.clone()
+            street: self.street.clone(),
+            cards: Vec::new()
         };
-        self.actions.push(forced_blind);
-        self.increment_turn();
+        self.record(forced_blind);
+        self.next_dealt_in();
+    }
+
+    /// Takes `ante` from a player, or whatever they have left if that is
+    /// less. A big blind ante pays for the whole table, so it is dead money
+    /// rather than part of the big blind's own contribution.
+    fn place_ante(&mut self, player: usize, ante: u64) {
+        let p = &mut self.players[player];
+        let ante = ante.min(p.chip_stack);
+        p.chip_stack -= ante;
+        if let Ante::BigBlind(_) = self.ante {
+            self.dead_money += ante;
+        } else {
+            p.total_bet += ante;
+        }
+        let id = p.id;
+        self.pot += ante;
+        self.record(Action{
+            action: ActionType::ANTE,
+            player: Some(id),
+            bet_size: ante,
+            street: self.street.clone(),
+            cards: Vec::new()
+        });
+    }
+
+    /// Blinds owed by players back from sitting out. A missed big blind is
+    /// posted live along with a dead small blind; the dead part goes in the
+    /// pot but does not count towards calling.
+    fn post_missed_blinds(&mut self) {
+        let half = self.big_blind/2;
+        let mut posted = Vec::new();
+        for p in self.players.iter_mut() {
+            if !p.dealt_in || !(p.missed_small_blind || p.missed_big_blind) {
+                continue;
+            }
+            let live = if p.missed_big_blind { self.big_blind.min(p.chip_stack) } else { 0 };
+            let dead = half.min(p.chip_stack - live);
+            p.chip_stack -= live + dead;
+            p.current_bet += live;
+            p.total_bet += live;
+            p.missed_small_blind = false;
+            p.missed_big_blind = false;
+            self.current_bet = self.current_bet.max(p.current_bet);
+            self.dead_money += dead;
+            self.pot += live + dead;
+            posted.push(Action{
+                action: ActionType::BLIND,
+                player: Some(p.id),
+                bet_size: live + dead,
+                street: self.street.clone(),
+                cards: Vec::new()
+            });
+        }
+        for action in posted {
+            self.record(action);
+        }
+    }
+
+    /// Flags players sitting out in a seat that the blinds passed over this
+    /// hand.
+    fn mark_missed_blinds(&mut self, small_seat: usize, big_seat: usize) {
+        let seats = self.max_seats;
+        let button = self.button as usize;
+        let after = |from: usize, seat: usize| (seat + seats - from) % seats;
+        for p in self.players.iter_mut().filter(|p| p.sitting_out) {
+            let from_button = after(button, p.seat);
+            if from_button > 0 && from_button <= after(button, small_seat) {
+                p.missed_small_blind = true;
+            }
+            let from_small = after(small_seat, p.seat);
+            if from_small > 0 && from_small < after(small_seat, big_seat) {
+                p.missed_big_blind = true;
+            }
+        }
+    }
+
+    /// Indices of the small blind (`None` when it is dead) and big blind for
+    /// this hand, and the seat the small blind is due from.
+    fn blind_positions(&self) -> (usize, Option<usize>, usize) {
+        let button = self.button_position();
+        if self.heads_up() {
+            let on_button = &self.players[button];
+            let small = if on_button.dealt_in && on_button.seat as u64 == self.button {
+                button
+            } else {
+                self.dealt_in_after(button)
+            };
+            return (self.players[small].seat, Some(small), self.dealt_in_after(small));
+        }
+        if let (ButtonRule::Dead, Some((_, last_big))) = (self.button_rule, self.last_blinds) {
+            let small = self.players.iter().position(|p| p.seat == last_big && p.dealt_in);
+            let big = self.dealt_in_after(self.seat_position(last_big as u64));
+            return (last_big, small, big);
+        }
+        let small = self.dealt_in_after(button);
+        return (self.players[small].seat, Some(small), self.dealt_in_after(small));
     }
 
 
-    pub fn force_blinds(&mut self) {
-        self.place_blind(self.big_blind/2);
+    /// Starts a hand: players who are sitting out are left out of it, and
+    /// the blinds are posted by the next two players dealt in after the
+    /// button, or as `button_rule` dictates once seats have emptied.
+    /// Heads-up the button posts the small blind and acts first preflop.
+    /// Antes are taken first, and players back from sitting out post any
+    /// blinds they missed last. Needs at least two players who are not
+    /// sitting out.
+    pub fn force_blinds(&mut self) -> Result<(), GameError> {
+        if self.players.iter().filter(|p| !p.sitting_out).count() < 2 {
+            return Err(GameError::NotEnoughPlayers)
+        }
+        for p in self.players.iter_mut() {
+            p.dealt_in = !p.sitting_out;
+        }
+        self.listeners.emit(GameEvent::HandStarted{hand_number: self.hand_number, button: self.button});
+        let (small_seat, small, big) = self.blind_positions();
+        match self.ante {
+            Ante::None => {}
+            Ante::PerPlayer(ante) => {
+                for i in 0..self.players.len() {
+                    if self.players[i].dealt_in {
+                        self.place_ante(i, ante);
+                    }
+                }
+            }
+            Ante::BigBlind(_) => {}
+        }
+        if let Some(small) = small {
+            self.turn_marker = small as u64;
+            self.place_blind(self.big_blind/2);
+        }
+        self.turn_marker = big as u64;
         self.place_blind(self.big_blind);
+        // the big blind covers the blind before the ante
+        if let Ante::BigBlind(ante) = self.ante {
+            self.place_ante(big, ante);
+        }
+        // posting in turn settles anything missed
+        for i in small.into_iter().chain([big]) {
+            self.players[i].missed_small_blind = false;
+            self.players[i].missed_big_blind = false;
+        }
+
+        let big_seat = self.players[big].seat;
+        self.mark_missed_blinds(small_seat, big_seat);
+        self.post_missed_blinds();
+        self.last_blinds = Some((small_seat, big_seat));
+        self.skip_all_in();
+        Ok(())
     }
 
 
-    fn set_big_blind(&mut self, blind: u64) {
+    pub fn set_big_blind(&mut self, blind: u64) {
         self.big_blind = blind;
     }
 
+    pub fn set_ante(&mut self, ante: Ante) {
+        self.ante = ante;
+    }
+
+    /// Posts a straddle of twice the big blind for `id`, between
+    /// `force_blinds` and `deal_hole_cards`. The straddle becomes the big
+    /// blind for the rest of preflop: raises must be at least its size,
+    /// action starts on the straddler's left and the straddler acts last.
+    pub fn straddle(&mut self, id: PlayerId) -> Result<(), GameError> {
+        let player = self.index_of(id)?;
+        if self.players.iter().any(|p| !p.hole_cards.is_empty()) {
+            return Err(GameError::CardsAlreadyDealt)
+        }
+        let straddled = self.actions.iter().any(|a| matches!(a.action, ActionType::STRADDLE));
+        if !self.hand_started() || straddled || self.heads_up() {
+            return Err(GameError::StraddleNotAllowed)
+        }
+        let p = &self.players[player];
+        let under_the_gun = player == self.turn_marker as usize;
+        let on_button = p.seat as u64 == self.button && p.current_bet == 0;
+        let allowed = (under_the_gun && self.allowed_straddles.contains(&StraddlePosition::UnderTheGun))
+            || (on_button && self.allowed_straddles.contains(&StraddlePosition::Button));
+        if !p.dealt_in || !allowed {
+            return Err(GameError::StraddleNotAllowed)
+        }
+
+        let straddle = self.big_blind * 2;
+        if p.chip_stack < straddle {
+            return Err(GameError::InsufficientChips{stack: p.chip_stack})
+        }
+        let p = &mut self.players[player];
+        p.chip_stack -= straddle;
+        p.current_bet = straddle;
+        p.total_bet += straddle;
+        self.pot += straddle;
+        self.current_bet = straddle;
+        self.previous_bet = straddle;
+        self.previous_raise = straddle;
+        self.record(Action{
+            action: ActionType::STRADDLE,
+            player: Some(id),
+            bet_size: straddle,
+            street: self.street.clone(),
+            cards: Vec::new()
+        });
+        self.turn_marker = self.dealt_in_after(player) as u64;
+        self.skip_all_in();
+        Ok(())
+    }
+
     fn increment_turn(&mut self) {
         if self.turn_marker < (self.players.len() - 1) as u64 {
             self.turn_marker += 1;
@@ -621,171 +1076,1358 @@ impl Game {
         }
     }
 
+    /// Moves the turn to the next player who has not folded and is not
+    /// all-in.
+    fn next_to_act(&mut self) {
+        for _ in 0..self.players.len() {
+            self.increment_turn();
+            let p = &self.players[self.turn_marker as usize];
+            if p.is_live() && p.chip_stack > 0 {
+                return;
+            }
+        }
+    }
+
+    /// Moves the turn on if forced bets have left the player to act all-in.
+    fn skip_all_in(&mut self) {
+        if self.players[self.turn_marker as usize].chip_stack == 0 {
+            self.next_to_act();
+        }
+    }
+
+    /// Moves the turn to the next player dealt into the hand.
+    fn next_dealt_in(&mut self) {
+        self.turn_marker = self.dealt_in_after(self.turn_marker as usize) as u64;
+    }
+
+    /// Index of the first player after index `i` who is dealt in.
+    fn dealt_in_after(&self, i: usize) -> usize {
+        let seats = self.players.len();
+        return (i + 1..=i + seats)
+            .map(|j| j % seats)
+            .find(|&j| self.players[j].dealt_in)
+            .unwrap_or(i);
+    }
+
+    /// Index of the player on the button, or of the nearest player before
+    /// it when the button seat is empty. Turn order is counted from here.
+    fn button_position(&self) -> usize {
+        return self.seat_position(self.button);
+    }
+
+    /// Index of the player in `seat`, or of the nearest player before it.
+    fn seat_position(&self, seat: u64) -> usize {
+        return self.players.iter()
+            .rposition(|p| p.seat as u64 <= seat)
+            .unwrap_or(self.players.len() - 1);
+    }
+
+    fn heads_up(&self) -> bool {
+        return self.players.iter().filter(|p| p.dealt_in).count() == 2;
+    }
+
+    /// Moves the button to the next seat whose player is not sitting out,
+    /// or under a dead button to the seat that owed the small blind.
     fn increment_button(&mut self) {
-        if self.button < (self.players.len() - 1) as u64 {
-            self.button += 1;
-        } else {
-            self.button = 0;
+        let active = || self.players.iter().filter(|p| !p.sitting_out);
+        // heads-up the button goes to last hand's big blind, which also
+        // keeps anyone from posting the big blind twice as the table shrinks
+        if active().count() == 2 {
+            if let Some(p) = active().find(|p| Some(p.seat) == self.last_blinds.map(|(_, big)| big)) {
+                self.button = p.seat as u64;
+                return;
+            }
+        }
+        if let (ButtonRule::Dead, Some((small, _))) = (self.button_rule, self.last_blinds) {
+            self.button = small as u64;
+            return;
+        }
+        let next = active().find(|p| p.seat as u64 > self.button).or_else(|| active().next());
+        if let Some(p) = next {
+            self.button = p.seat as u64;
         }
     }
 
 
-    fn decrement_turn(&mut self) {
-        if self.turn_marker < 1 {
-            self.turn_marker = (self.players.len() - 1) as u64;
-        } else {
-            self.turn_marker -= 1;
+    /// Validates and carries out `action` for `id`. This is the single
+    /// entry point for network layers, bots and replays.
+    pub fn apply(&mut self, id: PlayerId, action: PlayerAction) -> Result<(), GameError> {
+        match action {
+            PlayerAction::Fold => self.fold(id),
+            PlayerAction::Check => self.check(id),
+            PlayerAction::Call => self.call(id),
+            PlayerAction::Bet(amount) => self.bet(id, amount),
+            PlayerAction::RaiseTo(amount) => self.raise_to(id, amount),
+            PlayerAction::AllIn => self.all_in(id),
         }
     }
 
-    pub fn check(&mut self, name:String) -> Result<(), InvalidActionError> {
-        if self.players[self.turn_marker as usize].name != name {
-            return Err(InvalidActionError)
+    /// Checks that `id` is the player to act and can still act.
+    fn validate_turn(&self, id: PlayerId) -> Result<(), GameError> {
+        if !self.hand_started() {
+            return Err(GameError::HandNotStarted)
+        }
+        if self.hand_complete {
+            return Err(GameError::HandOver)
+        }
+        if self.street == GameStreet::SHOWDOWN {
+            return Err(GameError::WrongStreet{street: self.street.clone()})
         }
-        if self.players[self.turn_marker as usize].has_folded || self.players[self.turn_marker as usize].final_action  {
-            return Err(InvalidActionError)
+        if !self.players[self.index_of(id)?].dealt_in {
+            return Err(GameError::NotDealtIn)
         }
+        let player = &self.players[self.turn_marker as usize];
+        if player.id != id {
+            return Err(GameError::NotYourTurn{expected: player.id, got: id})
+        }
+        if player.has_folded {
+            return Err(GameError::AlreadyFolded)
+        }
+        if player.final_action {
+            return Err(GameError::AlreadyActed)
+        }
+        Ok(())
+    }
+
+    pub fn check(&mut self, id:PlayerId) -> Result<(), GameError> {
+        self.validate_turn(id)?;
         if self.players[self.turn_marker as usize].current_bet != self.current_bet {
-            return Err(InvalidActionError)
+            let to_call = self.current_bet - self.players[self.turn_marker as usize].current_bet;
+            return Err(GameError::CannotCheckFacingBet{to_call})
         }
         self.players[self.turn_marker as usize].final_action = true;
+        self.players[self.turn_marker as usize].acted_at = Some(self.current_bet);
         let action = Action{
             action: ActionType::CHECK,
-            player: self.players[self.turn_marker as usize].clone(),
+            player: Some(self.players[self.turn_marker as usize].id),
             bet_size: 0,
-            street: self.street.clone()
+            street: self.street.clone(),
+            cards: Vec::new()
         };
-        self.actions.push(action);
-        self.increment_turn();
+        self.record(action);
+        self.next_to_act();
         self.progress_street();
         Ok(())
     }
 
 
-    pub fn call(&mut self, name:String) -> Result<(), InvalidActionError> {
-        if self.players[self.turn_marker as usize].name != name {
-            return Err(InvalidActionError)
-        }
-        if self.players[self.turn_marker as usize].has_folded || self.players[self.turn_marker as usize].final_action  {
-            return Err(InvalidActionError)
-        }
+    /// Calls the current bet, or puts the rest of the player's chips in if
+    /// they cannot cover it.
+    pub fn call(&mut self, id:PlayerId) -> Result<(), GameError> {
+        self.validate_turn(id)?;
         if self.players[self.turn_marker as usize].current_bet >= self.current_bet {
-            return Err(InvalidActionError)
+            return Err(GameError::NothingToCall)
         }
-        let bet = self.current_bet - self.players[self.turn_marker as usize].current_bet;
+        let bet = (self.current_bet - self.players[self.turn_marker as usize].current_bet)
+            .min(self.players[self.turn_marker as usize].chip_stack);
         self.players[self.turn_marker as usize].chip_stack -= bet;
         self.players[self.turn_marker as usize].current_bet += bet;
+        self.players[self.turn_marker as usize].total_bet += bet;
         self.pot += bet;
         self.previous_bet = bet;
         self.players[self.turn_marker as usize].final_action = true;
+        self.players[self.turn_marker as usize].acted_at = Some(self.current_bet);
 
+        let all_in = self.players[self.turn_marker as usize].chip_stack == 0;
         let action = Action{
-            action: ActionType::CALL,
-            player: self.players[self.turn_marker as usize].clone(),
+            action: if all_in { ActionType::ALLIN } else { ActionType::CALL },
+            player: Some(self.players[self.turn_marker as usize].id),
             bet_size: bet,
-            street: self.street.clone()
+            street: self.street.clone(),
+            cards: Vec::new()
         };
-        self.actions.push(action);
-        self.increment_turn();
+        self.record(action);
+        self.next_to_act();
         self.progress_street();
         Ok(())
     }
 
-    pub fn fold(&mut self, name:String) -> Result<(), InvalidActionError> {
-        if self.players[self.turn_marker as usize].name != name {
-            return Err(InvalidActionError)
-        }
-        if self.players[self.turn_marker as usize].has_folded || self.players[self.turn_marker as usize].final_action  {
-            return Err(InvalidActionError)
-        }
+    pub fn fold(&mut self, id:PlayerId) -> Result<(), GameError> {
+        self.validate_turn(id)?;
         self.players[self.turn_marker as usize].has_folded = true;
         self.players[self.turn_marker as usize].final_action = true;
         let action = Action{
             action: ActionType::FOLD,
-            player: self.players[self.turn_marker as usize].clone(),
+            player: Some(self.players[self.turn_marker as usize].id),
             bet_size: 0,
-            street: self.street.clone()
+            street: self.street.clone(),
+            cards: Vec::new()
         };
-        self.actions.push(action);
-        self.increment_turn();
+        self.record(action);
+        if self.players.iter().filter(|p| p.is_live()).count() == 1 {
+            self.award_uncontested();
+            return Ok(())
+        }
+        self.next_to_act();
         self.progress_street();
         Ok(())
     }
 
     fn reset_final_action(&mut self) {
-        for mut p in self.players.iter_mut() {
+        for p in self.players.iter_mut() {
             p.final_action = false;
         }
     }
 
 
-    pub fn raise(&mut self, name:String, bet:u64) -> Result<(), InvalidActionError> {
-        if self.players[self.turn_marker as usize].name != name {
-            return Err(InvalidActionError)
+    /// Opens the betting on a street with a bet of `amount`.
+    pub fn bet(&mut self, id:PlayerId, amount:u64) -> Result<(), GameError> {
+        self.validate_turn(id)?;
+        let player = self.turn_marker as usize;
+        if self.current_bet > 0 {
+            return Err(GameError::CannotBetFacingBet{to_call: self.current_bet - self.players[player].current_bet})
         }
-        if self.players[self.turn_marker as usize].has_folded || self.players[self.turn_marker as usize].final_action  {
-            return Err(InvalidActionError)
+        self.validate_raise_to(player, amount)?;
+        self.wager(player, amount);
+        Ok(())
+    }
+
+    /// Raises the bet so the player has `amount` in front of them in total
+    /// on this street.
+    pub fn raise_to(&mut self, id:PlayerId, amount:u64) -> Result<(), GameError> {
+        self.validate_turn(id)?;
+        let player = self.turn_marker as usize;
+        if self.current_bet == 0 {
+            return Err(GameError::NoBetToRaise)
         }
-        if bet == self.players[self.turn_marker as usize].chip_stack {
-            self.current_bet = bet + self.players[self.turn_marker as usize].current_bet;
-            self.pot += bet;
-            self.players[self.turn_marker as usize].chip_stack -= bet;
-            if bet > self.previous_bet {
-                if bet - self.previous_bet > self.previous_raise {
-                    self.previous_raise = bet - self.previous_bet;
-                }
-            }
-            self.previous_bet = bet;
-            self.players[self.turn_marker as usize].current_bet += bet;
-            self.reset_final_action();
-            self.players[self.turn_marker as usize].final_action = true;
-            let action = Action{
-                action: ActionType::RAISE,
-                player: self.players[self.turn_marker as usize].clone(),
-                bet_size: bet,
-                street: self.street.clone()
-            };
-            self.actions.push(action);
-            self.increment_turn();
+        self.validate_raise_to(player, amount)?;
+        self.wager(player, amount);
+        Ok(())
+    }
 
-            return Ok(())
+    /// Puts all of the player's chips in: a bet or raise if that is more
+    /// than the current bet and raising is open, otherwise a call, for less
+    /// if that is all they have.
+    pub fn all_in(&mut self, id:PlayerId) -> Result<(), GameError> {
+        self.validate_turn(id)?;
+        let player = self.turn_marker as usize;
+        let all_in_to = self.players[player].current_bet + self.players[player].chip_stack;
+        let can_raise = self.legal_actions().is_some_and(|l| l.min_raise_to.is_some());
+        if all_in_to <= self.current_bet || !can_raise {
+            return self.call(id)
         }
-        if bet < self.big_blind {
-            return Err(InvalidActionError)
+        self.validate_raise_to(player, all_in_to)?;
+        self.wager(player, all_in_to);
+        Ok(())
+    }
+
+    /// Checks a bet or raise to `amount` against `legal_actions`. Going
+    /// all-in for less than a full raise is allowed.
+    fn validate_raise_to(&self, player: usize, amount: u64) -> Result<(), GameError> {
+        let stack = self.players[player].chip_stack;
+        if amount > self.players[player].current_bet + stack {
+            return Err(GameError::InsufficientChips{stack})
         }
-        if bet < self.previous_raise + self.current_bet || bet > self.players[self.turn_marker as usize].chip_stack {
-            return Err(InvalidActionError)
+        match self.legal_actions() {
+            Some(LegalActions{min_raise_to: Some(min), ..}) if amount < min => Err(GameError::RaiseTooSmall{min}),
+            Some(LegalActions{max_raise_to: Some(max), ..}) if amount > max => Err(GameError::RaiseTooLarge{max}),
+            Some(LegalActions{min_raise_to: Some(_), ..}) => Ok(()),
+            _ => Err(GameError::CannotRaise)
         }
+    }
 
-        self.current_bet = bet + self.players[self.turn_marker as usize].current_bet; // THIS IS WRONG??? why does player have current bet, does this need to be reset as well??
-        self.pot += bet;
-        self.players[self.turn_marker as usize].chip_stack -= bet;
-        self.players[self.turn_marker as usize].chip_stack -= bet;
-        self.previous_raise = bet - self.previous_bet;
-        self.previous_bet = bet;
-        self.players[self.turn_marker as usize].current_bet += bet;
-        self.reset_final_action();
-        self.players[self.turn_marker as usize].final_action = true;
-        let action = Action{
-            action: ActionType::RAISE,
-            player: self.players[self.turn_marker as usize].clone(),
-            bet_size: bet,
-            street: self.street.clone()
+    /// Bets or raises to a validated street total of `raise_to`.
+    fn wager(&mut self, player: usize, raise_to: u64) {
+        let to_call = self.current_bet - self.players[player].current_bet;
+        let (full_raise_to, _) = self.raise_bounds(to_call);
+        let p = &mut self.players[player];
+        let chips = raise_to - p.current_bet;
+        p.chip_stack -= chips;
+        p.current_bet = raise_to;
+        p.total_bet += chips;
+        self.pot += chips;
+
+        let action = if p.chip_stack == 0 {
+            ActionType::ALLIN
+        } else if self.current_bet == 0 {
+            ActionType::BET
+        } else {
+            ActionType::RAISE
         };
-        self.actions.push(action);
-        self.increment_turn();
+        // an all-in for less than a full raise leaves the raise size and the
+        // raise count as they were
+        if raise_to >= full_raise_to {
+            if self.current_bet > 0 {
+                self.raises_this_street += 1;
+            }
+            self.previous_raise = raise_to - self.current_bet;
+        }
+        self.previous_bet = chips;
+        self.current_bet = raise_to;
+        // everyone else must respond, though after a short all-in those who
+        // already acted may only call or fold
+        self.reset_final_action();
+        self.players[player].final_action = true;
+        self.players[player].acted_at = Some(raise_to);
+        self.record(Action{
+            action,
+            player: Some(self.players[player].id),
+            bet_size: chips,
+            street: self.street.clone(),
+            cards: Vec::new()
+        });
+        self.next_to_act();
         self.progress_street();
-        Ok(())
     }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::sync::mpsc::channel;
-    use crate::{card, card::Card, Game, Player};
+    use std::sync::{Arc, Mutex};
+    use crate::{card::Card, ActionType, Ante, BettingStructure, ButtonRule, Game, GameError, GameEvent, GameStreet, LegalActions, PlayerAction, PlayerId, Pot, StraddlePosition};
+    use crate::card::parse_cards;
     use crate::card::{Rank, Suit};
 
-}
+    fn game_with_stacks(stacks: &[u64]) -> Game {
+        let mut g = Game::new(1000, 100);
+        for (i, &stack) in stacks.iter().enumerate() {
+            g.add_player(format!("p{}", i)).unwrap();
+            g.players[i].chip_stack = stack;
+        }
+        g
+    }
+
+    fn commit(g: &mut Game, player: usize, amount: u64) {
+        g.players[player].chip_stack -= amount;
+        g.players[player].total_bet += amount;
+        g.pot += amount;
+    }
+
+    #[test]
+    fn side_pots_follow_all_in_contributions() {
+        let mut g = game_with_stacks(&[100, 1000, 1000, 1000]);
+        commit(&mut g, 0, 100);
+        commit(&mut g, 1, 500);
+        commit(&mut g, 2, 500);
+        commit(&mut g, 3, 200);
+        g.players[3].has_folded = true;
+
+        assert_eq!(g.pots(), vec![
+            Pot{amount: 400, eligible: vec![PlayerId(0), PlayerId(1), PlayerId(2)]},
+            Pot{amount: 900, eligible: vec![PlayerId(1), PlayerId(2)]},
+        ]);
+    }
+
+    #[test]
+    fn uncalled_chips_return_to_their_owner() {
+        let mut g = game_with_stacks(&[300, 1000]);
+        commit(&mut g, 0, 300);
+        commit(&mut g, 1, 800);
+
+        assert_eq!(g.pots(), vec![
+            Pot{amount: 600, eligible: vec![PlayerId(0), PlayerId(1)]},
+            Pot{amount: 500, eligible: vec![PlayerId(1)]},
+        ]);
+    }
+
+    #[test]
+    fn short_stack_only_wins_the_main_pot() {
+        let mut g = game_with_stacks(&[100, 1000, 1000]);
+        commit(&mut g, 0, 100);
+        commit(&mut g, 1, 500);
+        commit(&mut g, 2, 500);
+        g.board = vec![
+            Card::new(Rank::Two, Suit::Club),
+            Card::new(Rank::Seven, Suit::Diamond),
+            Card::new(Rank::Nine, Suit::Heart),
+            Card::new(Rank::Jack, Suit::Spade),
+            Card::new(Rank::Four, Suit::Club),
+        ];
+        g.players[0].hole_cards = vec![Card::new(Rank::Nine, Suit::Spade), Card::new(Rank::Nine, Suit::Club)];
+        g.players[1].hole_cards = vec![Card::new(Rank::Jack, Suit::Heart), Card::new(Rank::Ace, Suit::Club)];
+        g.players[2].hole_cards = vec![Card::new(Rank::Queen, Suit::Heart), Card::new(Rank::King, Suit::Club)];
+        g.street = GameStreet::SHOWDOWN;
+
+        g.find_winner().unwrap();
+        g.payout_winners().unwrap();
+
+        assert_eq!(g.players[0].chip_stack, 300);
+        assert_eq!(g.players[1].chip_stack, 500 + 800);
+        assert_eq!(g.players[2].chip_stack, 500);
+    }
+
+    #[test]
+    fn payout_ranks_hands_it_has_not_seen() {
+        let mut g = game_with_stacks(&[1000, 1000]);
+        commit(&mut g, 0, 200);
+        commit(&mut g, 1, 200);
+        assert_eq!(g.payout_winners(), Err(GameError::WrongStreet{street: GameStreet::PRE}));
+
+        g.board = vec![
+            Card::new(Rank::Two, Suit::Club),
+            Card::new(Rank::Seven, Suit::Diamond),
+            Card::new(Rank::Nine, Suit::Heart),
+            Card::new(Rank::Jack, Suit::Spade),
+            Card::new(Rank::Four, Suit::Club),
+        ];
+        g.players[0].hole_cards = vec![Card::new(Rank::Ace, Suit::Spade), Card::new(Rank::Ace, Suit::Club)];
+        g.players[1].hole_cards = vec![Card::new(Rank::King, Suit::Heart), Card::new(Rank::Queen, Suit::Club)];
+        g.street = GameStreet::SHOWDOWN;
+        g.payout_winners().unwrap();
+
+        assert_eq!(g.winners, vec![PlayerId(0)]);
+        assert_eq!(g.players[0].chip_stack, 1200);
+        assert_eq!(g.players[1].chip_stack, 800);
+    }
+
+    #[test]
+    fn odd_chip_goes_left_of_the_button() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        commit(&mut g, 0, 101);
+        commit(&mut g, 1, 101);
+        commit(&mut g, 2, 101);
+        g.players[0].has_folded = true;
+        g.board = vec![
+            Card::new(Rank::Ace, Suit::Club),
+            Card::new(Rank::King, Suit::Diamond),
+            Card::new(Rank::Queen, Suit::Heart),
+            Card::new(Rank::Jack, Suit::Spade),
+            Card::new(Rank::Ten, Suit::Club),
+        ];
+        g.players[1].hole_cards = vec![Card::new(Rank::Two, Suit::Spade), Card::new(Rank::Three, Suit::Club)];
+        g.players[2].hole_cards = vec![Card::new(Rank::Two, Suit::Heart), Card::new(Rank::Three, Suit::Diamond)];
+        g.button = 1;
+        g.street = GameStreet::SHOWDOWN;
+
+        g.find_winner().unwrap();
+        g.payout_winners().unwrap();
+
+        assert_eq!(g.players[2].chip_stack, 899 + 152);
+        assert_eq!(g.players[1].chip_stack, 899 + 151);
+    }
+
+    fn showdown(g: &mut Game, board: [(Rank, Suit); 5], hands: &[[(Rank, Suit); 2]]) {
+        g.board = board.iter().map(|&(r, s)| Card::new(r, s)).collect();
+        for (i, hand) in hands.iter().enumerate() {
+            g.players[i].hole_cards = hand.iter().map(|&(r, s)| Card::new(r, s)).collect();
+        }
+        g.street = GameStreet::SHOWDOWN;
+        g.find_winner().unwrap();
+    }
+
+    #[test]
+    fn high_card_kicker_decides_the_winner() {
+        use Rank::*;
+        use Suit::*;
+        let mut g = game_with_stacks(&[1000, 1000]);
+        showdown(&mut g, [(Two, Club), (Seven, Diamond), (Nine, Heart), (Jack, Spade), (Four, Club)],
+                 &[[(Ace, Spade), (Three, Diamond)], [(King, Heart), (Queen, Club)]]);
+        assert_eq!(g.winners, vec![PlayerId(0)]);
+    }
+
+    #[test]
+    fn pair_kicker_decides_the_winner() {
+        use Rank::*;
+        use Suit::*;
+        let mut g = game_with_stacks(&[1000, 1000]);
+        showdown(&mut g, [(Two, Club), (Seven, Diamond), (Nine, Heart), (Jack, Spade), (Four, Club)],
+                 &[[(Jack, Heart), (Eight, Diamond)], [(Jack, Club), (Ten, Club)]]);
+        assert_eq!(g.winners, vec![PlayerId(1)]);
+    }
+
+    #[test]
+    fn playing_the_board_chops() {
+        use Rank::*;
+        use Suit::*;
+        let mut g = game_with_stacks(&[1000, 1000]);
+        showdown(&mut g, [(Ace, Club), (King, Diamond), (Queen, Heart), (Jack, Spade), (Ten, Club)],
+                 &[[(Two, Heart), (Eight, Diamond)], [(Three, Club), (Four, Club)]]);
+        assert_eq!(g.winners.len(), 2);
+    }
 
+    #[test]
+    fn duplicate_names_are_paid_by_id() {
+        use Rank::*;
+        use Suit::*;
+        let mut g = game_with_stacks(&[900, 900]);
+        g.rename_player(PlayerId(0), String::from("sam")).unwrap();
+        g.rename_player(PlayerId(1), String::from("sam")).unwrap();
+        commit(&mut g, 0, 100);
+        commit(&mut g, 1, 100);
+        showdown(&mut g, [(Two, Club), (Seven, Diamond), (Nine, Heart), (Jack, Spade), (Four, Club)],
+                 &[[(Three, Heart), (Five, Diamond)], [(Ace, Club), (Ace, Diamond)]]);
+        g.payout_winners().unwrap();
+
+        assert_eq!(g.winners, vec![PlayerId(1)]);
+        assert_eq!(g.players[0].chip_stack, 800);
+        assert_eq!(g.players[1].chip_stack, 1000);
+    }
+
+    #[test]
+    fn session_seed_replays_every_hand() {
+        let mut first = Game::with_seed(1000, 100, 42);
+        let mut second = Game::with_seed(1000, 100, 42);
+        for g in [&mut first, &mut second] {
+            g.add_player(String::from("a")).unwrap();
+            g.add_player(String::from("b")).unwrap();
+        }
+        let mut decks = Vec::new();
+        for _ in 0..3 {
+            first.init_deck();
+            second.init_deck();
+            assert_eq!(first.deck, second.deck);
+            decks.push(first.deck.clone());
+            first.prep_next_hand();
+            second.prep_next_hand();
+        }
+        assert_ne!(decks[0], decks[1]);
+        assert_ne!(decks[1], decks[2]);
+    }
+
+    #[test]
+    fn preset_cards_are_dealt_through_the_normal_path() {
+        let mut g = Game::new(1000, 100);
+        let a = g.add_player(String::from("a")).unwrap();
+        let b = g.add_player(String::from("b")).unwrap();
+        g.set_hole_cards(a, parse_cards("As Ad").unwrap()).unwrap();
+        g.set_hole_cards(b, parse_cards("Ks Kd").unwrap()).unwrap();
+        g.set_board(parse_cards("Kh 7c 2d 9s 3h").unwrap()).unwrap();
+
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(a).unwrap();
+        g.check(b).unwrap();
+        for _ in 0..3 {
+            g.check(b).unwrap();
+            g.check(a).unwrap();
+        }
+        g.find_winner().unwrap();
+
+        assert_eq!(g.players[0].hole_cards, parse_cards("As Ad").unwrap());
+        assert_eq!(g.board, parse_cards("Kh 7c 2d 9s 3h").unwrap());
+        assert_eq!(g.winners, vec![b]);
+        for c in g.board.iter().chain(g.players.iter().flat_map(|p| p.hole_cards.iter())) {
+            assert!(!g.deck.contains(c));
+        }
+    }
+
+    #[test]
+    fn preset_cards_reject_duplicates() {
+        let mut g = game_with_stacks(&[1000, 1000]);
+        g.set_hole_cards(PlayerId(0), parse_cards("As Ad").unwrap()).unwrap();
+        assert_eq!(g.set_hole_cards(PlayerId(1), parse_cards("As Kd").unwrap()),
+                   Err(GameError::DuplicateCard(Card::new(Rank::Ace, Suit::Spade))));
+        assert!(g.set_hole_cards(PlayerId(1), parse_cards("Kd Kd").unwrap()).is_err());
+        assert!(g.set_board(parse_cards("Ad 7c 2d").unwrap()).is_err());
+        assert_eq!(g.set_hole_cards(PlayerId(7), parse_cards("Ks Kd").unwrap()),
+                   Err(GameError::UnknownPlayer(PlayerId(7))));
+        // replacing a player's own preset is fine
+        g.set_hole_cards(PlayerId(0), parse_cards("As Ah").unwrap()).unwrap();
+        g.set_board(parse_cards("Ad 7c 2d").unwrap()).unwrap();
+    }
+
+    #[test]
+    fn folding_to_the_big_blind_ends_the_hand() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.fold(PlayerId(0)).unwrap();
+        g.fold(PlayerId(1)).unwrap();
+
+        assert!(g.hand_complete);
+        assert!(g.board.is_empty());
+        assert_eq!(g.street, GameStreet::PRE);
+        assert_eq!(g.winners, vec![PlayerId(2)]);
+        assert_eq!(g.players[2].chip_stack, 1050);
+        assert_eq!(g.check(PlayerId(2)), Err(GameError::HandOver));
+        assert_eq!(g.find_winner(), Err(GameError::HandOver));
+        g.payout_winners().unwrap();
+        assert_eq!(g.players[2].chip_stack, 1050);
+    }
+
+    #[test]
+    fn folded_players_are_skipped_on_later_streets() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.fold(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        for _ in 0..3 {
+            g.check(PlayerId(1)).unwrap();
+            g.check(PlayerId(2)).unwrap();
+        }
+        assert_eq!(g.street, GameStreet::SHOWDOWN);
+        g.find_winner().unwrap();
+        g.payout_winners().unwrap();
+        assert!(g.hand_complete);
+        assert_eq!(g.players.iter().map(|p| p.chip_stack).sum::<u64>(), 3000);
+    }
+
+    #[test]
+    fn all_in_and_call_runs_out_the_board() {
+        let mut g = game_with_stacks(&[1000, 500]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.all_in(PlayerId(1)).unwrap();
+        assert_eq!(g.street, GameStreet::PRE);
+        g.call(PlayerId(0)).unwrap();
+
+        assert_eq!(g.street, GameStreet::SHOWDOWN);
+        assert_eq!(g.board.len(), 5);
+        let deals: Vec<usize> = g.actions.iter()
+            .filter(|a| matches!(a.action, ActionType::DEAL))
+            .map(|a| a.cards.len())
+            .collect();
+        assert_eq!(deals, vec![3, 1, 1]);
+
+        g.find_winner().unwrap();
+        g.payout_winners().unwrap();
+        assert_eq!(g.players.iter().map(|p| p.chip_stack).sum::<u64>(), 1500);
+    }
+
+    #[test]
+    fn all_in_calls_when_raising_is_closed() {
+        let mut g = game_with_stacks(&[500, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.all_in(PlayerId(0)).unwrap();
+        g.all_in(PlayerId(1)).unwrap();
+
+        assert_eq!(g.players[1].chip_stack, 500);
+        assert_eq!(g.street, GameStreet::SHOWDOWN);
+    }
+
+    #[test]
+    fn all_in_players_are_skipped_while_others_keep_betting() {
+        let mut g = game_with_stacks(&[100, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.all_in(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+
+        assert_eq!(g.street, GameStreet::FLOP);
+        g.check(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        assert_eq!(g.street, GameStreet::TURN);
+    }
+
+    #[test]
+    fn legal_actions_describe_the_player_to_act() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.legal_actions(), Some(LegalActions{
+            player: PlayerId(0), can_check: false, call_amount: 100, min_raise_to: Some(200), max_raise_to: Some(1000)
+        }));
+        g.raise_to(PlayerId(0), 300).unwrap();
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.player, PlayerId(1));
+        assert_eq!(legal.call_amount, 250);
+        assert_eq!(legal.min_raise_to, Some(500));
+        assert_eq!(legal.max_raise_to, Some(1000));
+        assert_eq!(g.raise_to(PlayerId(1), 450), Err(GameError::RaiseTooSmall{min: 500}));
+        assert_eq!(g.raise_to(PlayerId(1), 2050), Err(GameError::InsufficientChips{stack: 950}));
+    }
+
+    #[test]
+    fn nobody_acts_outside_a_hand() {
+        let mut g = Game::new(1000, 100);
+        g.add_player(String::from("a")).unwrap();
+        assert_eq!(g.legal_actions(), None);
+
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        assert_eq!(g.check(PlayerId(1)), Err(GameError::HandNotStarted));
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.fold(PlayerId(0)).unwrap();
+        g.fold(PlayerId(1)).unwrap();
+        g.prep_next_hand();
+
+        assert_eq!(g.legal_actions(), None);
+        assert_eq!(g.check(PlayerId(1)), Err(GameError::HandNotStarted));
+    }
+
+    #[test]
+    fn legal_actions_give_the_big_blind_its_option() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.player, PlayerId(2));
+        assert!(legal.can_check);
+        assert_eq!(legal.call_amount, 0);
+        assert_eq!(legal.min_raise_to, Some(200));
+    }
+
+    #[test]
+    fn legal_actions_cap_raises_at_the_stack() {
+        let mut g = game_with_stacks(&[150, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.min_raise_to, Some(150));
+        assert_eq!(legal.max_raise_to, Some(150));
+
+        g.all_in(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.call(PlayerId(2)).unwrap();
+        g.check(PlayerId(1)).unwrap();
+        g.bet(PlayerId(2), 850).unwrap();
+        // p1 can only call or fold: everyone else is all-in
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.call_amount, 850);
+        assert_eq!(legal.min_raise_to, None);
+    }
+
+    #[test]
+    fn errors_say_what_went_wrong() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.check(PlayerId(1)),
+                   Err(GameError::NotYourTurn{expected: PlayerId(0), got: PlayerId(1)}));
+        assert_eq!(g.check(PlayerId(0)), Err(GameError::CannotCheckFacingBet{to_call: 100}));
+        assert_eq!(g.find_winner(), Err(GameError::WrongStreet{street: GameStreet::PRE}));
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        assert_eq!(g.call(PlayerId(2)), Err(GameError::NothingToCall));
+        assert_eq!(GameError::RaiseTooSmall{min: 200}.to_string(), "raise must be to at least 200");
+    }
+
+    #[test]
+    fn apply_drives_a_hand_by_seat() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.apply(PlayerId(3), PlayerAction::Call), Err(GameError::UnknownPlayer(PlayerId(3))));
+        assert_eq!(g.apply(PlayerId(0), PlayerAction::Bet(300)), Err(GameError::CannotBetFacingBet{to_call: 100}));
+        g.apply(PlayerId(0), PlayerAction::Call).unwrap();
+        g.apply(PlayerId(1), PlayerAction::Fold).unwrap();
+        g.apply(PlayerId(2), PlayerAction::Check).unwrap();
+
+        assert_eq!(g.street, GameStreet::FLOP);
+        assert_eq!(g.apply(PlayerId(2), PlayerAction::RaiseTo(200)), Err(GameError::NoBetToRaise));
+        g.apply(PlayerId(2), PlayerAction::Bet(100)).unwrap();
+        assert_eq!(g.apply(PlayerId(0), PlayerAction::RaiseTo(150)), Err(GameError::RaiseTooSmall{min: 200}));
+        g.apply(PlayerId(0), PlayerAction::Call).unwrap();
+        assert_eq!(g.street, GameStreet::TURN);
+
+        g.apply(PlayerId(2), PlayerAction::Check).unwrap();
+        g.apply(PlayerId(0), PlayerAction::AllIn).unwrap();
+        assert_eq!(g.players[0].chip_stack, 0);
+    }
+
+    fn blind_posters(g: &Game) -> Vec<PlayerId> {
+        return g.actions.iter()
+            .filter(|a| matches!(a.action, ActionType::BLIND))
+            .filter_map(|a| a.player)
+            .collect();
+    }
+
+    #[test]
+    fn blinds_and_button_walk_around_empty_seats() {
+        let mut g = Game::new(1000, 100);
+        let a = g.seat_player(String::from("a"), 2).unwrap();
+        let b = g.seat_player(String::from("b"), 5).unwrap();
+        let c = g.seat_player(String::from("c"), 8).unwrap();
+        g.button = 2;
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(blind_posters(&g), vec![b, c]);
+        assert_eq!(g.legal_actions().unwrap().player, a);
+        for seat in [5, 8, 2] {
+            g.prep_next_hand();
+            assert_eq!(g.button, seat);
+        }
+    }
+
+    #[test]
+    fn sitting_out_players_are_skipped() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+        g.sit_out(PlayerId(1)).unwrap();
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert!(g.players[1].hole_cards.is_empty());
+        assert_eq!(blind_posters(&g), vec![PlayerId(2), PlayerId(3)]);
+        g.fold(PlayerId(0)).unwrap();
+        g.fold(PlayerId(2)).unwrap();
+        assert_eq!(g.winners, vec![PlayerId(3)]);
+
+        g.sit_in(PlayerId(1)).unwrap();
+        g.prep_next_hand();
+        assert_eq!(g.button, 1);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        assert_eq!(g.players[1].hole_cards.len(), 2);
+        // p1 was passed over for the small blind and posts it dead
+        assert_eq!(blind_posters(&g), vec![PlayerId(2), PlayerId(3), PlayerId(1)]);
+    }
+
+    #[test]
+    fn a_hand_needs_two_players() {
+        let mut g = game_with_stacks(&[1000, 1000]);
+        g.sit_out(PlayerId(1)).unwrap();
+        assert_eq!(g.force_blinds(), Err(GameError::NotEnoughPlayers));
+        assert!(g.actions.is_empty());
+    }
+
+    #[test]
+    fn joining_mid_hand_does_not_take_the_turn() {
+        let mut g = Game::new(1000, 100);
+        for seat in [0, 2, 4] {
+            g.seat_player(format!("p{}", seat), seat).unwrap();
+        }
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+
+        let late = g.seat_player(String::from("late"), 1).unwrap();
+        assert_eq!(g.legal_actions().unwrap().player, PlayerId(1));
+        assert_eq!(g.fold(late), Err(GameError::NotDealtIn));
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        assert_eq!(g.street, GameStreet::FLOP);
+    }
+
+    #[test]
+    fn seats_are_limited_by_table_size() {
+        let mut g = Game::new(1000, 100);
+        assert_eq!(g.set_max_seats(11), Err(GameError::InvalidTableSize(11)));
+        g.set_max_seats(2).unwrap();
+        g.add_player(String::from("a")).unwrap();
+        g.add_player(String::from("b")).unwrap();
+        assert_eq!(g.add_player(String::from("c")), Err(GameError::TableFull));
+        assert_eq!(g.seat_player(String::from("c"), 2), Err(GameError::NoSuchSeat(2)));
+
+        g.set_max_seats(6).unwrap();
+        assert_eq!(g.seat_player(String::from("c"), 1), Err(GameError::SeatTaken(1)));
+        g.remove_player(PlayerId(0)).unwrap();
+        let c = g.add_player(String::from("c")).unwrap();
+        assert_eq!(g.player(c).unwrap().seat, 0);
+    }
+
+    #[test]
+    fn heads_up_button_posts_small_blind_and_acts_first() {
+        let mut g = game_with_stacks(&[1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(blind_posters(&g), vec![PlayerId(0), PlayerId(1)]);
+        assert_eq!(g.players[0].current_bet, 50);
+        assert_eq!(g.call(PlayerId(1)), Err(GameError::NotYourTurn{expected: PlayerId(0), got: PlayerId(1)}));
+        g.call(PlayerId(0)).unwrap();
+        g.check(PlayerId(1)).unwrap();
+        // the button acts last after the flop
+        assert_eq!(g.legal_actions().unwrap().player, PlayerId(1));
+
+        g.fold(PlayerId(1)).unwrap();
+        g.prep_next_hand();
+        g.force_blinds().unwrap();
+        assert_eq!(g.button, 1);
+        assert_eq!(blind_posters(&g), vec![PlayerId(1), PlayerId(0)]);
+    }
+
+    #[test]
+    fn nobody_posts_the_big_blind_twice_going_heads_up() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        assert_eq!(blind_posters(&g), vec![PlayerId(1), PlayerId(2)]);
+        g.fold(PlayerId(0)).unwrap();
+        g.fold(PlayerId(1)).unwrap();
+
+        g.sit_out(PlayerId(0)).unwrap();
+        g.prep_next_hand();
+        g.force_blinds().unwrap();
+        assert_eq!(g.button, 2);
+        assert_eq!(blind_posters(&g), vec![PlayerId(2), PlayerId(1)]);
+    }
+
+    /// Plays a hand where everyone folds to the big blind.
+    fn fold_to_big_blind(g: &mut Game) {
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        while let Some(legal) = g.legal_actions() {
+            g.fold(legal.player).unwrap();
+        }
+    }
+
+    #[test]
+    fn busted_players_leave_the_table() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        fold_to_big_blind(&mut g);
+        g.players[1].chip_stack = 0;
+        g.prep_next_hand();
 
+        assert!(g.player(PlayerId(1)).is_none());
+        fold_to_big_blind(&mut g);
+        assert_eq!(blind_posters(&g), vec![PlayerId(2), PlayerId(0)]);
+    }
+
+    #[test]
+    fn dead_button_keeps_the_big_blind_moving() {
+        for (rule, button, blinds) in [
+            (ButtonRule::Moving, 2, vec![PlayerId(0), PlayerId(1)]),
+            (ButtonRule::Dead, 2, vec![PlayerId(0)]),
+        ] {
+            let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+            g.button_rule = rule;
+            fold_to_big_blind(&mut g);
+            assert_eq!(blind_posters(&g), vec![PlayerId(1), PlayerId(2)]);
+            g.prep_next_hand();
+            fold_to_big_blind(&mut g);
+            g.players[3].chip_stack = 0;
+            g.prep_next_hand();
+            fold_to_big_blind(&mut g);
+
+            // seat 3 busted in the big blind, so under a dead button nobody
+            // owes the small blind
+            assert_eq!(g.button, button);
+            assert_eq!(blind_posters(&g), blinds);
+        }
+    }
+
+    #[test]
+    fn missed_blinds_are_posted_on_return() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+        g.sit_out(PlayerId(2)).unwrap();
+        fold_to_big_blind(&mut g);
+        assert_eq!(blind_posters(&g), vec![PlayerId(1), PlayerId(3)]);
+        assert!(g.players[2].missed_big_blind);
+        g.prep_next_hand();
+        fold_to_big_blind(&mut g);
+        assert_eq!(blind_posters(&g), vec![PlayerId(3), PlayerId(0)]);
+
+        g.sit_in(PlayerId(2)).unwrap();
+        g.prep_next_hand();
+        g.init_deck();
+        g.force_blinds().unwrap();
+        assert_eq!(g.button, 2);
+        assert_eq!(blind_posters(&g), vec![PlayerId(3), PlayerId(0), PlayerId(2)]);
+        // big blind live, small blind dead
+        assert_eq!((g.players[2].current_bet, g.players[2].total_bet), (100, 100));
+        assert_eq!(g.pot, 300);
+        assert!(!g.players[2].missed_big_blind && !g.players[2].missed_small_blind);
+    }
+
+    #[test]
+    fn busting_the_last_player_to_act_leaves_a_valid_turn() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        fold_to_big_blind(&mut g);
+        g.players[2].chip_stack = 0;
+        g.turn_marker = 2;
+        g.prep_next_hand();
+
+        assert_eq!(g.players.len(), 2);
+        assert!((g.turn_marker as usize) < g.players.len());
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        assert!(g.legal_actions().is_some());
+    }
+
+    #[test]
+    fn missed_big_blind_is_live_over_a_short_big_blind() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+        g.sit_out(PlayerId(2)).unwrap();
+        fold_to_big_blind(&mut g);
+        g.prep_next_hand();
+        fold_to_big_blind(&mut g);
+        g.sit_in(PlayerId(2)).unwrap();
+        g.prep_next_hand();
+        g.players[0].chip_stack = 30;
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.current_bet, 100);
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        g.fold(PlayerId(3)).unwrap();
+        // the dead small blind goes to the main pot, not a level of its own
+        assert_eq!(g.pots(), vec![
+            Pot{amount: 170, eligible: vec![PlayerId(0), PlayerId(1), PlayerId(2)]},
+            Pot{amount: 160, eligible: vec![PlayerId(1), PlayerId(2)]},
+        ]);
+    }
+
+    fn antes(g: &Game) -> Vec<(PlayerId, u64)> {
+        return g.actions.iter()
+            .filter(|a| a.action() == ActionType::ANTE)
+            .map(|a| (a.player().unwrap(), a.bet_size()))
+            .collect();
+    }
+
+    #[test]
+    fn antes_go_in_the_pot_before_the_blinds() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.set_ante(Ante::PerPlayer(10));
+        g.init_deck();
+        g.force_blinds().unwrap();
+
+        assert_eq!(antes(&g), vec![(PlayerId(0), 10), (PlayerId(1), 10), (PlayerId(2), 10)]);
+        assert_eq!(g.pot, 180);
+        // antes are dead: the big blind is still what there is to call
+        assert_eq!(g.players[2].current_bet, 100);
+        assert_eq!(g.current_bet, 100);
+
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.set_ante(Ante::BigBlind(100));
+        g.init_deck();
+        g.force_blinds().unwrap();
+        assert_eq!(antes(&g), vec![(PlayerId(2), 100)]);
+        assert_eq!(g.players[2].chip_stack, 800);
+        assert_eq!(g.pot, 250);
+    }
+
+    #[test]
+    fn big_blind_ante_is_won_by_the_best_hand() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.set_ante(Ante::BigBlind(100));
+        g.set_hole_cards(PlayerId(0), parse_cards("As Ad").unwrap()).unwrap();
+        g.set_hole_cards(PlayerId(1), parse_cards("8h 6d").unwrap()).unwrap();
+        g.set_hole_cards(PlayerId(2), parse_cards("7c 2h").unwrap()).unwrap();
+        g.set_board(parse_cards("Kc Qd 9s 5h 3c").unwrap()).unwrap();
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        for _ in 0..3 {
+            g.check(PlayerId(1)).unwrap();
+            g.check(PlayerId(2)).unwrap();
+            g.check(PlayerId(0)).unwrap();
+        }
+
+        assert_eq!(g.pots(), vec![Pot{amount: 400, eligible: vec![PlayerId(0), PlayerId(1), PlayerId(2)]}]);
+        g.payout_winners().unwrap();
+        let stacks: Vec<u64> = g.players.iter().map(|p| p.chip_stack).collect();
+        assert_eq!(stacks, vec![1300, 900, 800]);
+
+        // a short big blind covers the blind before the ante
+        let mut g = game_with_stacks(&[1000, 1000, 150]);
+        g.set_ante(Ante::BigBlind(100));
+        g.force_blinds().unwrap();
+        assert_eq!(antes(&g), vec![(PlayerId(2), 50)]);
+        assert_eq!(g.players[2].current_bet, 100);
+    }
+
+    #[test]
+    fn players_all_in_from_the_ante_are_skipped() {
+        let mut g = game_with_stacks(&[10, 1000, 1000]);
+        g.set_ante(Ante::PerPlayer(10));
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.legal_actions().unwrap().player, PlayerId(1));
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        assert_eq!(g.street, GameStreet::FLOP);
+    }
+
+    #[test]
+    fn short_ante_and_blind_make_a_side_pot() {
+        let mut g = game_with_stacks(&[1000, 30, 1000]);
+        g.set_ante(Ante::PerPlayer(25));
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        assert_eq!(g.players[1].total_bet, 30);
+        g.call(PlayerId(0)).unwrap();
+
+        assert_eq!(g.pots(), vec![
+            Pot{amount: 90, eligible: vec![PlayerId(0), PlayerId(1), PlayerId(2)]},
+            Pot{amount: 190, eligible: vec![PlayerId(0), PlayerId(2)]},
+        ]);
+    }
+
+    #[test]
+    fn under_the_gun_straddler_acts_last() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        assert_eq!(g.straddle(PlayerId(3)), Err(GameError::StraddleNotAllowed));
+        g.allowed_straddles = vec![StraddlePosition::UnderTheGun];
+        assert_eq!(g.straddle(PlayerId(0)), Err(GameError::StraddleNotAllowed));
+        g.straddle(PlayerId(3)).unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.legal_actions(), Some(LegalActions{
+            player: PlayerId(0), can_check: false, call_amount: 200, min_raise_to: Some(400), max_raise_to: Some(1000)
+        }));
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.call(PlayerId(2)).unwrap();
+        assert_eq!(g.street, GameStreet::PRE);
+        assert!(g.legal_actions().unwrap().can_check);
+        g.check(PlayerId(3)).unwrap();
+        assert_eq!(g.street, GameStreet::FLOP);
+        assert_eq!(g.pot, 800);
+    }
+
+    #[test]
+    fn button_straddle_starts_action_with_the_small_blind() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000, 1000]);
+        g.allowed_straddles = vec![StraddlePosition::Button];
+        g.init_deck();
+        g.force_blinds().unwrap();
+        assert_eq!(g.straddle(PlayerId(3)), Err(GameError::StraddleNotAllowed));
+        g.straddle(PlayerId(0)).unwrap();
+        assert_eq!(g.straddle(PlayerId(0)), Err(GameError::StraddleNotAllowed));
+        g.deal_hole_cards();
+
+        assert_eq!(g.legal_actions().unwrap().player, PlayerId(1));
+        g.fold(PlayerId(1)).unwrap();
+        g.fold(PlayerId(2)).unwrap();
+        g.raise_to(PlayerId(3), 500).unwrap();
+        assert_eq!(g.legal_actions().unwrap().player, PlayerId(0));
+    }
+
+    #[test]
+    fn fixed_limit_raises_are_sized_and_capped() {
+        let mut g = game_with_stacks(&[2000, 2000, 2000, 2000]);
+        g.betting_structure = BettingStructure::FixedLimit{small_bet: 100, big_bet: 200, max_raises: 3};
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(200));
+        assert_eq!(g.legal_actions().unwrap().max_raise_to, Some(200));
+        assert_eq!(g.apply(PlayerId(3), PlayerAction::RaiseTo(300)), Err(GameError::RaiseTooLarge{max: 200}));
+        g.apply(PlayerId(3), PlayerAction::RaiseTo(200)).unwrap();
+        g.apply(PlayerId(0), PlayerAction::RaiseTo(300)).unwrap();
+        g.apply(PlayerId(1), PlayerAction::RaiseTo(400)).unwrap();
+        // the big blind was the bet, so that was the third raise
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, None);
+        g.apply(PlayerId(2), PlayerAction::Fold).unwrap();
+        assert_eq!(g.apply(PlayerId(3), PlayerAction::RaiseTo(500)), Err(GameError::CannotRaise));
+        g.apply(PlayerId(3), PlayerAction::Fold).unwrap();
+
+        // heads-up the cap is lifted
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(500));
+    }
+
+    #[test]
+    fn short_all_ins_do_not_count_towards_the_cap() {
+        let mut g = game_with_stacks(&[250, 2000, 2000, 2000]);
+        g.betting_structure = BettingStructure::FixedLimit{small_bet: 100, big_bet: 200, max_raises: 3};
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.apply(PlayerId(3), PlayerAction::RaiseTo(200)).unwrap();
+        g.apply(PlayerId(0), PlayerAction::AllIn).unwrap();
+        let legal = g.legal_actions().unwrap();
+        g.apply(PlayerId(1), PlayerAction::RaiseTo(legal.min_raise_to.unwrap())).unwrap();
+
+        // the big blind and two raises: one raise is still left
+        assert!(g.legal_actions().unwrap().min_raise_to.is_some());
+    }
+
+    #[test]
+    fn fixed_limit_uses_the_big_bet_on_the_turn() {
+        let mut g = game_with_stacks(&[2000, 2000, 2000]);
+        g.betting_structure = BettingStructure::FixedLimit{small_bet: 100, big_bet: 200, max_raises: 3};
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        assert_eq!(g.legal_actions().unwrap().max_raise_to, Some(100));
+        for p in [1, 2, 0] {
+            g.check(PlayerId(p)).unwrap();
+        }
+
+        assert_eq!(g.street, GameStreet::TURN);
+        let legal = g.legal_actions().unwrap();
+        assert_eq!((legal.min_raise_to, legal.max_raise_to), (Some(200), Some(200)));
+    }
+
+    #[test]
+    fn pot_limit_caps_raises_at_the_pot_after_calling() {
+        let mut g = game_with_stacks(&[5000, 5000, 5000]);
+        g.betting_structure = BettingStructure::PotLimit;
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+
+        // 150 in blinds plus the 100 call, on top of the 100 to call
+        assert_eq!(g.legal_actions().unwrap().max_raise_to, Some(350));
+        assert_eq!(g.apply(PlayerId(0), PlayerAction::RaiseTo(400)), Err(GameError::RaiseTooLarge{max: 350}));
+        g.apply(PlayerId(0), PlayerAction::RaiseTo(350)).unwrap();
+        let legal = g.legal_actions().unwrap();
+        assert_eq!((legal.call_amount, legal.min_raise_to, legal.max_raise_to), (300, Some(600), Some(1150)));
+    }
+
+    #[test]
+    fn raises_take_chips_once_and_set_the_next_minimum() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.raise_to(PlayerId(0), 300).unwrap();
+        g.raise_to(PlayerId(1), 500).unwrap();
+
+        assert_eq!(g.players[0].chip_stack, 700);
+        assert_eq!(g.players[1].chip_stack, 500);
+        assert_eq!(g.pot, 900);
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(700));
+        g.call(PlayerId(2)).unwrap();
+        g.call(PlayerId(0)).unwrap();
+
+        assert_eq!(g.street, GameStreet::FLOP);
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(100));
+        assert_eq!(g.raise_to(PlayerId(1), 200), Err(GameError::NoBetToRaise));
+        g.bet(PlayerId(1), 150).unwrap();
+        assert_eq!(g.bet(PlayerId(2), 300), Err(GameError::CannotBetFacingBet{to_call: 150}));
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(300));
+    }
+
+    #[test]
+    fn short_stack_can_call_all_in_for_less() {
+        let mut g = game_with_stacks(&[60, 1000, 1000]);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        assert_eq!(g.legal_actions().unwrap().call_amount, 60);
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+
+        assert!(matches!(g.actions.iter().find(|a| a.player == Some(PlayerId(0))).unwrap().action, ActionType::ALLIN));
+        assert_eq!(g.street, GameStreet::FLOP);
+        assert_eq!(g.pots(), vec![
+            Pot{amount: 180, eligible: vec![PlayerId(0), PlayerId(1), PlayerId(2)]},
+            Pot{amount: 80, eligible: vec![PlayerId(1), PlayerId(2)]},
+        ]);
+    }
+
+    /// Everyone calls preflop, then `p1` opens the flop for 100.
+    fn flop_bet(stacks: &[u64]) -> Game {
+        let mut g = game_with_stacks(stacks);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        while g.street == GameStreet::PRE {
+            let legal = g.legal_actions().unwrap();
+            if legal.can_check {
+                g.check(legal.player).unwrap();
+            } else {
+                g.call(legal.player).unwrap();
+            }
+        }
+        g.bet(PlayerId(1), 100).unwrap();
+        g
+    }
+
+    #[test]
+    fn short_all_in_does_not_reopen_raising() {
+        let mut g = flop_bet(&[1000, 1000, 250]);
+        g.all_in(PlayerId(2)).unwrap();
+
+        // p0 has not acted yet and may still raise
+        assert_eq!(g.legal_actions().unwrap().min_raise_to, Some(250));
+        g.call(PlayerId(0)).unwrap();
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.player, PlayerId(1));
+        assert_eq!(legal.call_amount, 50);
+        assert_eq!(legal.min_raise_to, None);
+        assert_eq!(g.raise_to(PlayerId(1), 400), Err(GameError::CannotRaise));
+        g.call(PlayerId(1)).unwrap();
+        assert_eq!(g.street, GameStreet::TURN);
+    }
+
+    #[test]
+    fn short_all_ins_that_add_up_to_a_full_raise_reopen_raising() {
+        let mut g = flop_bet(&[1000, 1000, 250, 300]);
+        g.all_in(PlayerId(2)).unwrap();
+        g.all_in(PlayerId(3)).unwrap();
+        g.call(PlayerId(0)).unwrap();
+
+        let legal = g.legal_actions().unwrap();
+        assert_eq!(legal.player, PlayerId(1));
+        assert_eq!(legal.min_raise_to, Some(300));
+    }
+
+    fn event_log(g: &mut Game) -> Arc<Mutex<Vec<GameEvent>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        g.subscribe(Box::new(move |e: &GameEvent| sink.lock().unwrap().push(e.clone())));
+        log
+    }
+
+    #[test]
+    fn events_describe_a_hand_folded_to_the_big_blind() {
+        let mut g = game_with_stacks(&[1000, 1000, 1000]);
+        let log = event_log(&mut g);
+        fold_to_big_blind(&mut g);
+
+        let mut events = log.lock().unwrap().clone();
+        let dealt = events.iter().filter(|e| matches!(e, GameEvent::HoleCardsDealt{cards, ..} if cards.len() == 2)).count();
+        assert_eq!(dealt, 3);
+        events.retain(|e| !matches!(e, GameEvent::HoleCardsDealt{..}));
+        assert_eq!(events, vec![
+            GameEvent::HandStarted{hand_number: 1, button: 0},
+            GameEvent::BlindPosted{player: PlayerId(1), amount: 50},
+            GameEvent::BlindPosted{player: PlayerId(2), amount: 100},
+            GameEvent::PlayerActed{player: PlayerId(0), action: PlayerAction::Fold, amount: 0},
+            GameEvent::PlayerActed{player: PlayerId(1), action: PlayerAction::Fold, amount: 0},
+            GameEvent::PotAwarded{amount: 150, winners: vec![PlayerId(2)]},
+            GameEvent::HandEnded{hand_number: 1},
+        ]);
+    }
+
+    #[test]
+    fn events_follow_an_all_in_to_elimination() {
+        let mut g = game_with_stacks(&[1000, 200]);
+        g.set_hole_cards(PlayerId(0), parse_cards("As Ad").unwrap()).unwrap();
+        g.set_hole_cards(PlayerId(1), parse_cards("7c 2d").unwrap()).unwrap();
+        g.set_board(parse_cards("Kh 9c 4s 3h Jd").unwrap()).unwrap();
+        let log = event_log(&mut g);
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.all_in(PlayerId(1)).unwrap();
+        g.call(PlayerId(0)).unwrap();
+        g.find_winner().unwrap();
+        g.payout_winners().unwrap();
+        g.prep_next_hand();
+
+        let events = log.lock().unwrap();
+        assert!(events.contains(&GameEvent::PlayerActed{player: PlayerId(1), action: PlayerAction::AllIn, amount: 100}));
+        let streets: Vec<GameStreet> = events.iter()
+            .filter_map(|e| match e { GameEvent::StreetDealt{street, ..} => Some(street.clone()), _ => None })
+            .collect();
+        assert_eq!(streets, vec![GameStreet::FLOP, GameStreet::TURN, GameStreet::RIVER]);
+        assert_eq!(events[events.len() - 3..], [
+            GameEvent::PotAwarded{amount: 400, winners: vec![PlayerId(0)]},
+            GameEvent::HandEnded{hand_number: 1},
+            GameEvent::PlayerEliminated{player: PlayerId(1)},
+        ]);
+    }
+}
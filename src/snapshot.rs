@@ -0,0 +1,311 @@
+//! Saving a game and loading it back. With the `serde` feature a `Snapshot`
+//! can be written in any serde format; `Game::restore` checks it before
+//! handing back a playable game, so a tampered or truncated save is refused
+//! rather than played.
+
+use std::collections::HashSet;
+use crate::card::Card;
+use crate::{ActionType, Game, GameError};
+
+/// Bumped whenever the layout of a saved `Game` changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A game frozen at one point in a hand. Listeners are not saved.
+///
+/// `version` is written first and read before anything else, so a snapshot
+/// in a layout this crate cannot read still loads, with no `game`, and
+/// `Game::restore` reports its version.
+#[derive(Debug,Clone)]
+pub struct Snapshot {
+    pub version: u32,
+    pub game: Option<Game>,
+}
+
+impl Game {
+    pub fn snapshot(&self) -> Snapshot {
+        return Snapshot{version: SNAPSHOT_VERSION, game: Some(self.clone())};
+    }
+
+    /// Rebuilds a game from `snapshot`. Once a deck is in play every one of
+    /// the 52 cards must be accounted for exactly once across the deck,
+    /// board, burns and hands. The pot must equal what the players have
+    /// committed this hand, and stacks and pot together must hold every chip
+    /// brought to the table.
+    pub fn restore(snapshot: Snapshot) -> Result<Game, GameError> {
+        let game = match snapshot.game {
+            Some(game) if snapshot.version == SNAPSHOT_VERSION => game,
+            _ => return Err(GameError::UnsupportedSnapshot{version: snapshot.version}),
+        };
+        game.check_seating()?;
+        game.check_cards()?;
+        game.check_betting()?;
+
+        let committed: u64 = game.players.iter().map(|p| p.total_bet).sum::<u64>() + game.dead_money;
+        if game.pot != committed {
+            return Err(GameError::ChipsDoNotAddUp{pot: game.pot, committed})
+        }
+        // a paid-out pot has already been added to the winners' stacks
+        let in_pot = if game.hand_complete { 0 } else { game.pot };
+        let got = game.players.iter().map(|p| p.chip_stack).sum::<u64>() + in_pot;
+        if got != game.table_chips {
+            return Err(GameError::ChipCountChanged{expected: game.table_chips, got})
+        }
+        return Ok(game);
+    }
+
+    fn check_betting(&self) -> Result<(), GameError> {
+        if let Some(p) = self.players.iter().find(|p| p.current_bet > p.total_bet) {
+            return Err(GameError::CorruptSnapshot(format!("{} has bet more this street than this hand", p.id)))
+        }
+        let top_bet = self.players.iter().map(|p| p.current_bet).max().unwrap_or(0);
+        if self.current_bet != top_bet {
+            return Err(GameError::CorruptSnapshot(format!("the bet is {} but the largest bet is {}", self.current_bet, top_bet)))
+        }
+        if let Some(p) = self.players.iter().find(|p| p.acted_at.is_some_and(|level| level > self.current_bet)) {
+            return Err(GameError::CorruptSnapshot(format!("{} acted on a bet that was never made", p.id)))
+        }
+        let raises = self.actions.iter()
+            .filter(|a| a.street == self.street && matches!(a.action, ActionType::RAISE | ActionType::ALLIN))
+            .count();
+        if self.raises_this_street as usize > raises {
+            return Err(GameError::CorruptSnapshot(format!("{} raises counted but {} made", self.raises_this_street, raises)))
+        }
+        Ok(())
+    }
+
+    fn check_seating(&self) -> Result<(), GameError> {
+        if self.max_seats < 2 || self.max_seats > 10 {
+            return Err(GameError::InvalidTableSize(self.max_seats))
+        }
+        for pair in self.players.windows(2) {
+            if pair[0].seat >= pair[1].seat {
+                return Err(GameError::CorruptSnapshot(String::from("players are not in seat order")))
+            }
+        }
+        if let Some(p) = self.players.iter().find(|p| p.seat >= self.max_seats) {
+            return Err(GameError::NoSuchSeat(p.seat))
+        }
+        let mut ids = HashSet::new();
+        for p in self.players.iter() {
+            if p.id.0 >= self.next_player_id || !ids.insert(p.id) {
+                return Err(GameError::CorruptSnapshot(format!("{} is seated twice or was never seated", p.id)))
+            }
+        }
+        if !self.players.is_empty() && self.turn_marker as usize >= self.players.len() {
+            return Err(GameError::CorruptSnapshot(format!("turn marker {} is past the last player", self.turn_marker)))
+        }
+        Ok(())
+    }
+
+    fn check_cards(&self) -> Result<(), GameError> {
+        let dealt = self.players.iter().any(|p| !p.hole_cards.is_empty());
+        let mut cards: Vec<&Card> = self.deck.iter().collect();
+        if dealt {
+            cards.extend(self.board.iter());
+            cards.extend(self.burned.iter());
+            cards.extend(self.players.iter().flat_map(|p| p.hole_cards.iter()));
+            // preset board cards were taken out of the deck when hands were dealt
+            cards.extend(self.preset_board.iter().skip(self.board.len()));
+        } else if !self.board.is_empty() || !self.burned.is_empty() {
+            return Err(GameError::CorruptSnapshot(String::from("board dealt before hole cards")))
+        }
+        if !cards.is_empty() && cards.len() != 52 {
+            return Err(GameError::WrongCardCount{expected: 52, got: cards.len()})
+        }
+        let mut seen = HashSet::new();
+        if let Some(c) = cards.into_iter().find(|c| !seen.insert(*c)) {
+            return Err(GameError::DuplicateCard(c.clone()))
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod format {
+    use std::fmt;
+    use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{self, SerializeStruct};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use super::{Snapshot, SNAPSHOT_VERSION};
+
+    impl Serialize for Snapshot {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let game = self.game.as_ref()
+                .ok_or_else(|| ser::Error::custom(format!("cannot write a version {} snapshot", self.version)))?;
+            let mut state = serializer.serialize_struct("Snapshot", 2)?;
+            state.serialize_field("version", &self.version)?;
+            state.serialize_field("game", game)?;
+            state.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Snapshot {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Snapshot, D::Error> {
+            deserializer.deserialize_struct("Snapshot", &["version", "game"], SnapshotVisitor)
+        }
+    }
+
+    struct SnapshotVisitor;
+
+    impl<'de> Visitor<'de> for SnapshotVisitor {
+        type Value = Snapshot;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a versioned game snapshot")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Snapshot, A::Error> {
+            let version: u32 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let game = if version == SNAPSHOT_VERSION {
+                Some(seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?)
+            } else {
+                seq.next_element::<IgnoredAny>()?;
+                None
+            };
+            Ok(Snapshot{version, game})
+        }
+
+        // the version must come first so it can decide how to read the game
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Snapshot, A::Error> {
+            if map.next_key::<String>()?.as_deref() != Some("version") {
+                return Err(de::Error::missing_field("version"))
+            }
+            let version: u32 = map.next_value()?;
+            if map.next_key::<String>()?.as_deref() != Some("game") {
+                return Err(de::Error::missing_field("game"))
+            }
+            let game = if version == SNAPSHOT_VERSION {
+                Some(map.next_value()?)
+            } else {
+                map.next_value::<IgnoredAny>()?;
+                None
+            };
+            Ok(Snapshot{version, game})
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::parse_cards;
+    use crate::{Game, GameError, PlayerId, SNAPSHOT_VERSION};
+
+    fn flop() -> Game {
+        let mut g = Game::new(1000, 100);
+        g.add_player(String::from("a")).unwrap();
+        g.add_player(String::from("b")).unwrap();
+        g.add_player(String::from("c")).unwrap();
+        g.init_deck_from_seed(7);
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.call(PlayerId(0)).unwrap();
+        g.call(PlayerId(1)).unwrap();
+        g.check(PlayerId(2)).unwrap();
+        g
+    }
+
+    #[test]
+    fn restores_a_hand_in_progress() {
+        let g = flop();
+        let mut restored = Game::restore(g.snapshot()).unwrap();
+
+        assert_eq!(restored.board, g.board);
+        assert_eq!(restored.pot, 300);
+        assert_eq!(restored.legal_actions(), g.legal_actions());
+        restored.check(PlayerId(1)).unwrap();
+        assert!(Game::restore(Game::new(1000, 100).snapshot()).is_ok());
+    }
+
+    #[test]
+    fn restores_around_players_sitting_out() {
+        let mut g = Game::new(1000, 100);
+        for name in ["a", "b", "c", "d"] {
+            g.add_player(String::from(name)).unwrap();
+        }
+        g.set_hole_cards(PlayerId(3), parse_cards("As Ad").unwrap()).unwrap();
+        g.sit_out(PlayerId(3)).unwrap();
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g.fold(PlayerId(0)).unwrap();
+        g.fold(PlayerId(1)).unwrap();
+        assert!(Game::restore(g.snapshot()).is_ok());
+
+        let removed = g.remove_player(PlayerId(0)).unwrap();
+        assert_eq!(removed.chip_stack, 1000);
+        g.prep_next_hand();
+        assert!(Game::restore(g.snapshot()).is_ok());
+    }
+
+    #[test]
+    fn rejects_cards_that_do_not_add_up() {
+        let mut snapshot = flop().snapshot();
+        let game = snapshot.game.as_mut().unwrap();
+        game.deck[0] = game.board[0].clone();
+        let card = game.board[0].clone();
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::DuplicateCard(card));
+
+        let mut snapshot = flop().snapshot();
+        snapshot.game.as_mut().unwrap().deck.pop();
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::WrongCardCount{expected: 52, got: 51});
+
+        let mut snapshot = flop().snapshot();
+        let game = snapshot.game.as_mut().unwrap();
+        game.players[0].hole_cards = game.players[1].hole_cards.clone();
+        assert!(matches!(Game::restore(snapshot), Err(GameError::DuplicateCard(_))));
+    }
+
+    #[test]
+    fn rejects_chips_that_do_not_add_up() {
+        let mut snapshot = flop().snapshot();
+        snapshot.game.as_mut().unwrap().pot += 50;
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::ChipsDoNotAddUp{pot: 350, committed: 300});
+
+        let mut snapshot = flop().snapshot();
+        snapshot.game.as_mut().unwrap().players[0].chip_stack += 1_000_000;
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::ChipCountChanged{expected: 3000, got: 1_003_000});
+
+        let mut snapshot = flop().snapshot();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::UnsupportedSnapshot{version: SNAPSHOT_VERSION + 1});
+    }
+
+    #[test]
+    fn rejects_betting_that_could_not_have_happened() {
+        let mut g = flop();
+        g.bet(PlayerId(1), 100).unwrap();
+
+        let mut snapshot = g.snapshot();
+        snapshot.game.as_mut().unwrap().players[1].acted_at = Some(500);
+        assert!(matches!(Game::restore(snapshot), Err(GameError::CorruptSnapshot(_))));
+
+        let mut snapshot = g.snapshot();
+        snapshot.game.as_mut().unwrap().current_bet = 50;
+        assert!(matches!(Game::restore(snapshot), Err(GameError::CorruptSnapshot(_))));
+
+        let mut snapshot = g.snapshot();
+        snapshot.game.as_mut().unwrap().raises_this_street = 1;
+        assert!(matches!(Game::restore(snapshot), Err(GameError::CorruptSnapshot(_))));
+        assert!(Game::restore(g.snapshot()).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_json() {
+        let g = flop();
+        let json = serde_json::to_string(&g.snapshot()).unwrap();
+        let restored = Game::restore(serde_json::from_str(&json).unwrap()).unwrap();
+
+        assert_eq!(restored.deck, g.deck);
+        assert_eq!(restored.players, g.players);
+        assert_eq!(restored.spectator_view(), g.spectator_view());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reads_the_version_before_the_game() {
+        let json = r#"{"version": 0, "game": {"seats": "an older layout"}}"#;
+        let snapshot: crate::Snapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(Game::restore(snapshot).unwrap_err(), GameError::UnsupportedSnapshot{version: 0});
+    }
+}
@@ -0,0 +1,173 @@
+//! Redacted projections of a `Game` that are safe to hand to clients. Hole
+//! cards are only included for their owner, or for everyone once they have
+//! been shown at showdown, and the deck is never included.
+
+use crate::card::Card;
+use crate::{Game, GameError, GameStreet, LegalActions, Player, PlayerId};
+
+/// One occupied seat as others at the table see it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub struct SeatView {
+    pub id: PlayerId,
+    pub name: String,
+    pub seat: usize,
+    pub chip_stack: u64,
+    /// Chips in front of the player on this street.
+    pub current_bet: u64,
+    /// Chips committed this hand, blinds and antes included.
+    pub total_bet: u64,
+    pub has_folded: bool,
+    pub sitting_out: bool,
+    /// `None` unless the viewer may see them.
+    pub hole_cards: Option<Vec<Card>>,
+}
+
+/// Everything public about the table.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub struct SpectatorView {
+    pub hand_number: u64,
+    pub button: u64,
+    pub street: GameStreet,
+    pub board: Vec<Card>,
+    pub pot: u64,
+    pub current_bet: u64,
+    pub seats: Vec<SeatView>,
+    pub to_act: Option<PlayerId>,
+    pub winners: Vec<PlayerId>,
+}
+
+/// The table as one player sees it: the public view plus their own cards
+/// and, on their turn, what they may do.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug,Eq,PartialEq,Clone)]
+pub struct PlayerView {
+    pub player: PlayerId,
+    pub hole_cards: Vec<Card>,
+    pub table: SpectatorView,
+    pub legal_actions: Option<LegalActions>,
+}
+
+fn seat_view(p: &Player, viewer: Option<PlayerId>) -> SeatView {
+    let visible = p.shown || Some(p.id) == viewer;
+    return SeatView{
+        id: p.id,
+        name: p.name.clone(),
+        seat: p.seat,
+        chip_stack: p.chip_stack,
+        current_bet: p.current_bet,
+        total_bet: p.total_bet,
+        has_folded: p.has_folded,
+        sitting_out: p.sitting_out,
+        hole_cards: if visible { Some(p.hole_cards.clone()) } else { None },
+    };
+}
+
+impl Game {
+    fn table_view(&self, viewer: Option<PlayerId>) -> SpectatorView {
+        return SpectatorView{
+            hand_number: self.hand_number,
+            button: self.button,
+            street: self.street.clone(),
+            board: self.board.clone(),
+            pot: self.pot,
+            current_bet: self.current_bet,
+            seats: self.players.iter().map(|p| seat_view(p, viewer)).collect(),
+            to_act: self.legal_actions().map(|l| l.player),
+            winners: self.winners.clone(),
+        };
+    }
+
+    /// The table as someone not playing sees it.
+    pub fn spectator_view(&self) -> SpectatorView {
+        return self.table_view(None);
+    }
+
+    /// The table as `id` sees it.
+    pub fn player_view(&self, id: PlayerId) -> Result<PlayerView, GameError> {
+        let player = self.player(id).ok_or(GameError::UnknownPlayer(id))?;
+        let legal_actions = self.legal_actions().filter(|l| l.player == id);
+        return Ok(PlayerView{
+            player: id,
+            hole_cards: player.hole_cards.clone(),
+            table: self.table_view(Some(id)),
+            legal_actions,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::parse_cards;
+    use crate::{Game, GameError, PlayerId};
+
+    fn heads_up() -> Game {
+        let mut g = Game::new(1000, 100);
+        g.add_player(String::from("a")).unwrap();
+        g.add_player(String::from("b")).unwrap();
+        g.set_hole_cards(PlayerId(0), parse_cards("As Ad").unwrap()).unwrap();
+        g.set_hole_cards(PlayerId(1), parse_cards("Ks Kd").unwrap()).unwrap();
+        g.init_deck();
+        g.force_blinds().unwrap();
+        g.deal_hole_cards();
+        g
+    }
+
+    #[test]
+    fn players_only_see_their_own_cards() {
+        let g = heads_up();
+        let view = g.player_view(PlayerId(0)).unwrap();
+
+        assert_eq!(view.hole_cards, parse_cards("As Ad").unwrap());
+        assert_eq!(view.table.seats[0].hole_cards, Some(parse_cards("As Ad").unwrap()));
+        assert_eq!(view.table.seats[1].hole_cards, None);
+        assert!(view.legal_actions.is_some());
+        assert!(g.player_view(PlayerId(1)).unwrap().legal_actions.is_none());
+        assert!(g.spectator_view().seats.iter().all(|s| s.hole_cards.is_none()));
+        assert_eq!(g.player_view(PlayerId(5)), Err(GameError::UnknownPlayer(PlayerId(5))));
+    }
+
+    #[test]
+    fn cards_are_revealed_at_showdown() {
+        let mut g = heads_up();
+        g.call(PlayerId(0)).unwrap();
+        g.check(PlayerId(1)).unwrap();
+        for _ in 0..3 {
+            g.check(PlayerId(1)).unwrap();
+            g.check(PlayerId(0)).unwrap();
+        }
+        assert!(g.spectator_view().seats.iter().all(|s| s.hole_cards.is_none()));
+        g.find_winner().unwrap();
+
+        let view = g.spectator_view();
+        assert_eq!(view.seats[1].hole_cards, Some(parse_cards("Ks Kd").unwrap()));
+        assert_eq!(view.board.len(), 5);
+        assert_eq!(view.pot, 200);
+    }
+
+    #[test]
+    fn nobody_is_to_act_at_an_idle_table() {
+        let mut g = Game::new(1000, 100);
+        g.add_player(String::from("a")).unwrap();
+        assert_eq!(g.spectator_view().to_act, None);
+
+        let mut g = heads_up();
+        g.fold(PlayerId(0)).unwrap();
+        g.prep_next_hand();
+        let view = g.spectator_view();
+        assert_eq!(view.to_act, None);
+        assert_eq!(view.hand_number, 2);
+    }
+
+    #[test]
+    fn folded_hands_stay_hidden() {
+        let mut g = heads_up();
+        g.fold(PlayerId(0)).unwrap();
+
+        let view = g.player_view(PlayerId(1)).unwrap();
+        assert_eq!(view.table.winners, vec![PlayerId(1)]);
+        assert_eq!(view.table.seats[0].hole_cards, None);
+        assert!(view.table.seats[0].has_folded);
+    }
+}